@@ -0,0 +1,253 @@
+use std::ops::{Deref, DerefMut};
+
+use super::{
+	data_flow_graph::DataFlowGraph,
+	link::{Id, Iter, Link},
+};
+
+/// A region under construction, given to the closures of the scoped builders.
+/// It dereferences to the graph so nodes can be added to it.
+pub struct Region<'a, T> {
+	nodes: &'a mut DataFlowGraph<T>,
+	argument: Id,
+	index: usize,
+	arity: usize,
+}
+
+impl<'a, T> Region<'a, T> {
+	/// Returns the index of the region within its parent.
+	#[inline]
+	#[must_use]
+	pub const fn index(&self) -> usize {
+		self.index
+	}
+
+	/// Returns the [`Argument`] node of the region.
+	///
+	/// [`Argument`]: super::node::Argument
+	#[inline]
+	#[must_use]
+	pub const fn argument(&self) -> Id {
+		self.argument
+	}
+
+	/// Returns the [`Link`] of the `port`th value passed into the region.
+	///
+	/// # Panics
+	///
+	/// Panics if the region has no such value.
+	#[inline]
+	#[must_use]
+	pub fn input(&self, port: usize) -> Link {
+		assert!(port < self.arity, "region has no port {port}");
+
+		Link {
+			node: self.argument,
+			port: port.try_into().unwrap(),
+		}
+	}
+
+	/// Returns an iterator over the [`Link`] of every value passed into the region.
+	#[inline]
+	#[must_use]
+	pub fn inputs(&self) -> Iter {
+		Iter {
+			node: self.argument,
+			ports: 0..self.arity.try_into().unwrap(),
+		}
+	}
+}
+
+impl<'a, T> Deref for Region<'a, T> {
+	type Target = DataFlowGraph<T>;
+
+	#[inline]
+	fn deref(&self) -> &Self::Target {
+		self.nodes
+	}
+}
+
+impl<'a, T> DerefMut for Region<'a, T> {
+	#[inline]
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		self.nodes
+	}
+}
+
+impl<T> DataFlowGraph<T> {
+	fn build_region<R, F>(&mut self, index: usize, arity: usize, build: F) -> (Id, R)
+	where
+		F: FnOnce(&mut Region<'_, T>) -> R,
+	{
+		let argument = self.add_argument().node;
+		let mut region = Region {
+			nodes: self,
+			argument,
+			index,
+			arity,
+		};
+
+		(argument, build(&mut region))
+	}
+
+	/// Adds a [`Node::Gamma`] node with `regions` regions and returns its [`Link`].
+	/// Every region is built by `build`, which returns the results of the region.
+	///
+	/// [`Node::Gamma`]: super::node::Node::Gamma
+	#[must_use]
+	pub fn gamma<F>(
+		&mut self,
+		predicate: Link,
+		mut parameters: Vec<Link>,
+		regions: usize,
+		mut build: F,
+	) -> Link
+	where
+		F: FnMut(&mut Region<'_, T>) -> Vec<Link>,
+	{
+		let arity = parameters.len();
+		let built: Vec<_> = (0..regions)
+			.map(|index| self.build_region(index, arity, &mut build))
+			.collect();
+
+		let arguments = built.iter().map(|region| region.0).collect();
+		let results = built.into_iter().map(|region| region.1).collect();
+
+		parameters.push(predicate);
+
+		self.add_gamma(parameters, arguments, results)
+	}
+
+	/// Adds a [`Node::Theta`] node and returns its [`Link`].
+	/// The region is built by `build`, which returns the next values of the
+	/// loop variables and whether to repeat the loop.
+	///
+	/// # Panics
+	///
+	/// Panics if there are not as many loop variables as parameters.
+	///
+	/// [`Node::Theta`]: super::node::Node::Theta
+	#[must_use]
+	pub fn theta<F>(&mut self, parameters: Vec<Link>, build: F) -> Link
+	where
+		F: FnOnce(&mut Region<'_, T>) -> (Vec<Link>, Link),
+	{
+		let (argument, (mut results, predicate)) = self.build_region(0, parameters.len(), build);
+
+		assert_eq!(results.len(), parameters.len(), "loop variable mismatch");
+
+		results.push(predicate);
+
+		self.add_theta(parameters, argument, results)
+	}
+
+	/// Adds a [`Node::Lambda`] node called with `inputs` inputs and returns its [`Link`].
+	/// The region is built by `build`, which returns the results of the function.
+	///
+	/// [`Node::Lambda`]: super::node::Node::Lambda
+	#[must_use]
+	pub fn lambda<F>(&mut self, parameters: Vec<Link>, inputs: usize, build: F) -> Link
+	where
+		F: FnOnce(&mut Region<'_, T>) -> Vec<Link>,
+	{
+		let arity = parameters.len() + inputs;
+		let (argument, results) = self.build_region(0, arity, build);
+
+		self.add_lambda(parameters, inputs, argument, results)
+	}
+
+	/// Adds a [`Node::Phi`] node with `results` results and returns its [`Link`].
+	/// The region is built by `build`, which can refer to the results through
+	/// the ports following the parameters.
+	///
+	/// # Panics
+	///
+	/// Panics if `build` does not return `results` results.
+	///
+	/// [`Node::Phi`]: super::node::Node::Phi
+	#[must_use]
+	pub fn phi<F>(&mut self, parameters: Vec<Link>, results: usize, build: F) -> Link
+	where
+		F: FnOnce(&mut Region<'_, T>) -> Vec<Link>,
+	{
+		let arity = parameters.len() + results;
+		let (argument, list) = self.build_region(0, arity, build);
+
+		assert_eq!(list.len(), results, "recursive result mismatch");
+
+		self.add_phi(parameters, argument, list)
+	}
+
+	/// Adds a [`Node::Delta`] node and returns its [`Link`].
+	/// The region is built by `build`, which returns the value of the global.
+	///
+	/// [`Node::Delta`]: super::node::Node::Delta
+	#[must_use]
+	pub fn delta<F>(&mut self, parameters: Vec<Link>, build: F) -> Link
+	where
+		F: FnOnce(&mut Region<'_, T>) -> Link,
+	{
+		let (argument, result) = self.build_region(0, parameters.len(), build);
+
+		self.add_delta(parameters, argument, vec![result])
+	}
+
+	/// Adds a [`Node::Omega`] node as the root of the graph and returns its [`Id`].
+	/// The region is built by `build`, which receives the `imports` in order
+	/// and returns the values to export under the `exports` names.
+	///
+	/// # Panics
+	///
+	/// Panics if `build` does not return one result per export.
+	///
+	/// [`Node::Omega`]: super::node::Node::Omega
+	pub fn omega<F>(&mut self, imports: Vec<String>, exports: Vec<String>, build: F) -> Id
+	where
+		F: FnOnce(&mut Region<'_, T>) -> Vec<Link>,
+	{
+		let (argument, results) = self.build_region(0, imports.len(), build);
+
+		assert_eq!(results.len(), exports.len(), "export mismatch");
+
+		self.add_omega(imports, argument, exports, results)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		collection::data_flow_graph::DataFlowGraph, fixture::Simple, verify::verifier::Verifier,
+	};
+
+	#[test]
+	fn test_builds_nested_regions() {
+		let mut nodes = DataFlowGraph::<Simple>::new();
+
+		let node_0 = nodes.add_simple(Simple(Vec::new()));
+		let node_1 = nodes.theta(vec![node_0, node_0], |theta| {
+			let counter = theta.input(0);
+			let input = theta.input(1);
+			let predicate = theta.add_simple(Simple(vec![counter]));
+			let value = theta.gamma(predicate, vec![input], 2, |gamma| {
+				let inputs = gamma.inputs().collect();
+
+				if gamma.index() == 0 {
+					vec![gamma.input(0)]
+				} else {
+					vec![gamma.add_simple(Simple(inputs))]
+				}
+			});
+
+			(vec![counter, value], predicate)
+		});
+		let node_2 = nodes.delta(vec![node_1], |delta| delta.input(0));
+
+		let theta = nodes[node_1.node].as_theta().unwrap();
+		let gamma = theta.results[1].node;
+
+		assert_eq!(nodes.outputs(node_1.node).count(), 2);
+		assert_eq!(nodes.outputs(node_2.node).count(), 1);
+		assert_eq!(nodes[gamma].as_gamma().unwrap().parameters.len(), 2);
+		assert!(Verifier::new().run(&nodes).is_empty());
+	}
+}
@@ -1,13 +1,192 @@
+use std::{
+	borrow::Cow,
+	collections::{HashMap, HashSet},
+};
+
 use arena::collection::Arena;
 use list::resizable::Resizable;
 
+use crate::visit::{
+	depth_first_searcher::{DepthFirstSearcher, Event},
+	region_finder::RegionFinder,
+};
+
 use super::{
-	link::{Id, Link},
-	node::{Gamma, Lambda, Node, Phi, Theta},
+	link::{Id, Iter, Link},
+	node::{
+		Argument, Arity, Delta, Gamma, Lambda, Node, Omega, Parameters, ParametersMut, Phi, Theta,
+	},
+	users::{Use, Users},
 };
 
+#[derive(Default)]
+struct Liveness {
+	nodes: HashSet<Id>,
+	ports: HashSet<Link>,
+	results: HashSet<(Id, usize, usize)>,
+	queue: Vec<Link>,
+}
+
+impl Liveness {
+	fn mark_result(&mut self, results: &[Vec<Link>], id: Id, region: usize, index: usize) {
+		if let Some(&link) = results.get(region).and_then(|list| list.get(index)) {
+			self.results.insert((id, region, index));
+			self.queue.push(link);
+		}
+	}
+
+	fn mark_node<T: Parameters>(&mut self, nodes: &Arena<Id, Node<T>>, id: Id) {
+		let Some(node) = nodes.get(id) else { return };
+
+		if !self.nodes.insert(id) {
+			return;
+		}
+
+		self.queue.extend(node.parameters().copied());
+
+		for &argument in node.as_arguments().unwrap_or_default() {
+			self.mark_node(nodes, argument);
+		}
+
+		// The predicate is needed as long as the loop runs.
+		if let Node::Theta(theta) = node {
+			let predicate = theta.results.len().wrapping_sub(1);
+
+			self.mark_result(std::slice::from_ref(&theta.results), id, 0, predicate);
+		}
+	}
+
+	fn mark_port<T: Parameters>(&mut self, nodes: &Arena<Id, Node<T>>, link: Link) {
+		let Some(node) = nodes.get(link.node) else {
+			return;
+		};
+		let port = usize::from(link.port);
+
+		self.mark_node(nodes, link.node);
+
+		match node {
+			Node::Simple(_) | Node::Omega(_) => {}
+			Node::Argument(argument) => {
+				// Loop and recursion variables feed back into their own region.
+				let parent = argument.parent;
+				let index = match nodes.get(parent) {
+					Some(Node::Theta(_)) => Some(port),
+					Some(Node::Phi(phi)) => port.checked_sub(phi.parameters.len()),
+					_ => None,
+				};
+
+				self.mark_node(nodes, parent);
+
+				if let Some(index) = index {
+					let results = nodes[parent].as_results().unwrap_or_default();
+
+					self.mark_result(results, parent, 0, index);
+				}
+			}
+			Node::Lambda(_) | Node::Delta(_) => {
+				// Functions and globals are only ever used whole.
+				let results = node.as_results().unwrap_or_default();
+
+				for index in 0..results[0].len() {
+					self.mark_result(results, link.node, 0, index);
+				}
+			}
+			Node::Gamma(_) | Node::Theta(_) | Node::Phi(_) => {
+				let results = node.as_results().unwrap_or_default();
+
+				for region in 0..results.len() {
+					self.mark_result(results, link.node, region, port);
+				}
+			}
+		}
+	}
+
+	fn run<T, I>(&mut self, nodes: &Arena<Id, Node<T>>, roots: I)
+	where
+		T: Parameters,
+		I: IntoIterator<Item = Id>,
+	{
+		for id in roots {
+			let Some(node) = nodes.get(id) else { continue };
+			let results = node.as_results().unwrap_or_default();
+
+			self.mark_node(nodes, id);
+
+			for (region, list) in results.iter().enumerate() {
+				for index in 0..list.len() {
+					self.mark_result(results, id, region, index);
+				}
+			}
+		}
+
+		while let Some(link) = self.queue.pop() {
+			if self.ports.insert(link) {
+				self.mark_port(nodes, link);
+			}
+		}
+	}
+}
+
+// Links to nodes missing from the map may alias other nodes once their `Id`s are reused.
+fn dangle_node<T: ParametersMut>(node: &mut Node<T>, map: &HashMap<Id, Id>) {
+	let dangle = |link: &mut Link| {
+		if !map.contains_key(&link.node) {
+			*link = Link::dangling();
+		}
+	};
+
+	node.parameters_mut().for_each(dangle);
+
+	if let Some(results) = node.as_mut_results() {
+		results.iter_mut().flatten().for_each(dangle);
+	}
+
+	if let Some(arguments) = node.as_mut_arguments() {
+		for id in arguments.iter_mut().filter(|id| !map.contains_key(id)) {
+			*id = Id::dangling();
+		}
+	}
+}
+
+fn remap_node<T: ParametersMut>(node: &mut Node<T>, map: &HashMap<Id, Id>) {
+	let remap = |link: &mut Link| {
+		if let Some(&id) = map.get(&link.node) {
+			link.node = id;
+		}
+	};
+
+	node.parameters_mut().for_each(remap);
+
+	if let Some(results) = node.as_mut_results() {
+		results.iter_mut().flatten().for_each(remap);
+	}
+
+	if let Some(arguments) = node.as_mut_arguments() {
+		for id in arguments {
+			*id = map.get(id).copied().unwrap_or(*id);
+		}
+	}
+
+	if let Some(argument) = node.as_mut_argument() {
+		argument.parent = map.get(&argument.parent).copied().unwrap_or(Id::dangling());
+	}
+}
+
+/// A graph of nodes that may optionally keep track of the users of every [`Link`].
+///
+/// User tracking only sees changes made through the methods of the graph,
+/// so editing nodes through [`DataFlowGraph::nodes_mut`] requires a call to
+/// [`DataFlowGraph::track_users`] afterwards to stay correct.
+///
+/// The graph may also have an [`Omega`] node as its root, which is always kept
+/// alive by garbage collection.
 pub struct DataFlowGraph<T> {
 	nodes: Arena<Id, Node<T>>,
+	users: Option<Users>,
+	root: Option<Id>,
+
+	// Simple nodes added while users are tracked, listed before the users are next read or changed.
+	unlisted: Vec<Id>,
 }
 
 impl<T> DataFlowGraph<T> {
@@ -17,7 +196,13 @@ impl<T> DataFlowGraph<T> {
 	pub fn new() -> Self {
 		let nodes = Arena::new();
 
-		Self { nodes }
+		Self {
+			nodes,
+			users: None,
+			root: None,
+
+			unlisted: Vec::new(),
+		}
 	}
 
 	/// Creates a new, empty graph with the specified capacity.
@@ -26,7 +211,13 @@ impl<T> DataFlowGraph<T> {
 	pub fn with_capacity(capacity: usize) -> Self {
 		let nodes = Arena::with_capacity(capacity);
 
-		Self { nodes }
+		Self {
+			nodes,
+			users: None,
+			root: None,
+
+			unlisted: Vec::new(),
+		}
 	}
 
 	/// Returns a reference to the inner [`Arena`] of the graph.
@@ -43,61 +234,818 @@ impl<T> DataFlowGraph<T> {
 		&mut self.nodes
 	}
 
+	/// Returns the [`Omega`] node at the root of the graph, if any.
+	#[inline]
+	#[must_use]
+	pub const fn root(&self) -> Option<Id> {
+		self.root
+	}
+
+	/// Sets the [`Omega`] node at the root of the graph.
+	#[inline]
+	pub fn set_root(&mut self, root: Option<Id>) {
+		self.root = root;
+	}
+
+	/// Stops tracking the users of the graph.
+	pub fn forget_users(&mut self) {
+		self.users = None;
+		self.unlisted.clear();
+	}
+
 	/// Adds a [`Node::Simple`] node to the graph and returns its [`Link`].
 	#[inline]
 	#[must_use]
 	pub fn add_simple<U: Into<T>>(&mut self, data: U) -> Link {
 		let node = Node::Simple(data.into());
+		let id = self.nodes.insert(node);
+
+		if self.users.is_some() {
+			self.unlisted.push(id);
+		}
+
+		id.into()
+	}
+
+	/// Adds a [`Node::Argument`] node to the graph and returns its [`Link`].
+	///
+	/// The node belongs to no region until it is passed to one of the compound constructors.
+	#[inline]
+	#[must_use]
+	pub fn add_argument(&mut self) -> Link {
+		let node = Node::Argument(Argument {
+			parent: Id::dangling(),
+			region: 0,
+		});
 
 		self.nodes.insert(node).into()
 	}
 
+	fn adopt_arguments(&mut self, parent: Id) {
+		let arguments = self.nodes[parent]
+			.as_arguments()
+			.unwrap_or_default()
+			.to_vec();
+
+		for (region, id) in arguments.into_iter().enumerate() {
+			let argument = self
+				.nodes
+				.get_mut(id)
+				.and_then(Node::as_mut_argument)
+				.expect("region start should be an `Argument` of the graph");
+
+			argument.parent = parent;
+			argument.region = region;
+		}
+	}
+
+	fn add_compound(&mut self, node: Node<T>) -> Link {
+		let id = self.nodes.insert(node);
+
+		if let Some(users) = &mut self.users {
+			let node = &self.nodes[id];
+			let parameters = node.as_parameters().unwrap_or_default();
+
+			users.insert_links(id, parameters, node.as_results().unwrap_or_default());
+		}
+
+		self.adopt_arguments(id);
+
+		id.into()
+	}
+
 	/// Adds a [`Node::Gamma`] node to the graph and returns its [`Link`].
+	///
+	/// # Panics
+	///
+	/// Panics if any of the `arguments` is not an [`Argument`] node of the graph.
 	#[inline]
 	#[must_use]
-	pub fn add_gamma(&mut self, parameters: Vec<Link>, results: Resizable<Vec<Link>, 2>) -> Link {
+	pub fn add_gamma(
+		&mut self,
+		parameters: Vec<Link>,
+		arguments: Resizable<Id, 2>,
+		results: Resizable<Vec<Link>, 2>,
+	) -> Link {
 		let node = Node::Gamma(Gamma {
 			parameters,
+			arguments,
 			results,
 		});
 
-		self.nodes.insert(node).into()
+		self.add_compound(node)
 	}
 
 	/// Adds a [`Node::Theta`] node to the graph and returns its [`Link`].
+	///
+	/// # Panics
+	///
+	/// Panics if `argument` is not an [`Argument`] node of the graph.
 	#[inline]
 	#[must_use]
-	pub fn add_theta(&mut self, parameters: Vec<Link>, results: Vec<Link>) -> Link {
+	pub fn add_theta(&mut self, parameters: Vec<Link>, argument: Id, results: Vec<Link>) -> Link {
 		let node = Node::Theta(Theta {
 			parameters,
+			argument,
 			results,
 		});
 
-		self.nodes.insert(node).into()
+		self.add_compound(node)
 	}
 
 	/// Adds a [`Node::Phi`] node to the graph and returns its [`Link`].
+	///
+	/// # Panics
+	///
+	/// Panics if `argument` is not an [`Argument`] node of the graph.
 	#[inline]
 	#[must_use]
-	pub fn add_phi(&mut self, parameters: Vec<Link>, results: Vec<Link>) -> Link {
+	pub fn add_phi(&mut self, parameters: Vec<Link>, argument: Id, results: Vec<Link>) -> Link {
 		let node = Node::Phi(Phi {
 			parameters,
+			argument,
 			results,
 		});
 
-		self.nodes.insert(node).into()
+		self.add_compound(node)
 	}
 
 	/// Adds a [`Node::Lambda`] node to the graph and returns its [`Link`].
+	///
+	/// # Panics
+	///
+	/// Panics if `argument` is not an [`Argument`] node of the graph.
 	#[inline]
 	#[must_use]
-	pub fn add_lambda(&mut self, parameters: Vec<Link>, results: Vec<Link>) -> Link {
+	pub fn add_lambda(
+		&mut self,
+		parameters: Vec<Link>,
+		inputs: usize,
+		argument: Id,
+		results: Vec<Link>,
+	) -> Link {
 		let node = Node::Lambda(Lambda {
 			parameters,
+			inputs,
+			argument,
 			results,
 		});
 
-		self.nodes.insert(node).into()
+		self.add_compound(node)
+	}
+
+	/// Adds a [`Node::Delta`] node to the graph and returns its [`Link`].
+	///
+	/// # Panics
+	///
+	/// Panics if `argument` is not an [`Argument`] node of the graph.
+	#[inline]
+	#[must_use]
+	pub fn add_delta(&mut self, parameters: Vec<Link>, argument: Id, results: Vec<Link>) -> Link {
+		let node = Node::Delta(Delta {
+			parameters,
+			argument,
+			results,
+		});
+
+		self.add_compound(node)
+	}
+
+	/// Adds a [`Node::Omega`] node to the graph, makes it the root and returns its [`Id`].
+	/// The previous root, if any, is kept as a regular node.
+	///
+	/// # Panics
+	///
+	/// Panics if `argument` is not an [`Argument`] node of the graph.
+	#[inline]
+	pub fn add_omega(
+		&mut self,
+		imports: Vec<String>,
+		argument: Id,
+		exports: Vec<String>,
+		results: Vec<Link>,
+	) -> Id {
+		let node = Node::Omega(Omega {
+			imports,
+			argument,
+			exports,
+			results,
+		});
+		let id = self.add_compound(node).node;
+
+		self.root = Some(id);
+
+		id
+	}
+}
+
+impl<T: Arity> DataFlowGraph<T> {
+	/// Returns the number of results of the node, or `None` if it does not exist.
+	/// [`Argument`] nodes take theirs from their parent.
+	#[must_use]
+	pub fn arity(&self, id: Id) -> Option<usize> {
+		match self.nodes.get(id)? {
+			Node::Argument(argument) => self.nodes.get(argument.parent)?.argument_arity(),
+			node => node.arity(),
+		}
+	}
+
+	/// Returns an iterator over the [`Link`] of every result of the node.
+	/// Ports past `u16::MAX` cannot be linked to and are left out.
+	#[must_use]
+	pub fn outputs(&self, id: Id) -> Iter {
+		let ports = self
+			.arity(id)
+			.map_or(0, |arity| arity.try_into().unwrap_or(u16::MAX));
+
+		Iter {
+			node: id,
+			ports: 0..ports,
+		}
+	}
+}
+
+impl<T: Parameters> DataFlowGraph<T> {
+	/// Returns the [`Users`] of the graph if they are being tracked.
+	///
+	/// Nodes added by [`DataFlowGraph::add_simple`] are only listed once the graph is
+	/// next changed, while [`DataFlowGraph::users_of`] always includes them.
+	#[inline]
+	#[must_use]
+	pub const fn users(&self) -> Option<&Users> {
+		self.users.as_ref()
+	}
+
+	/// Returns the users of the [`Link`].
+	///
+	/// # Panics
+	///
+	/// Panics if users are not being tracked.
+	#[must_use]
+	pub fn users_of(&self, link: Link) -> Cow<'_, [Use]> {
+		let listed = self
+			.users
+			.as_ref()
+			.expect("users should be tracked")
+			.users_of(link);
+
+		if self.unlisted.is_empty() {
+			return Cow::Borrowed(listed);
+		}
+
+		let mut uses = listed.to_vec();
+
+		for &id in &self.unlisted {
+			if self.nodes.get(id).is_some() {
+				self.find_uses(id, link, &mut uses);
+			}
+		}
+
+		Cow::Owned(uses)
+	}
+
+	/// Starts tracking the users of the graph, rebuilding them if already tracked.
+	pub fn track_users(&mut self) {
+		let mut users = self.users.take().unwrap_or_default();
+
+		users.build(self);
+
+		self.users = Some(users);
+		self.unlisted.clear();
+	}
+
+	fn list_users(&mut self) {
+		let Some(users) = &mut self.users else {
+			return;
+		};
+
+		for id in self.unlisted.drain(..) {
+			if let Some(node) = self.nodes.get(id) {
+				users.insert_node(id, node);
+			}
+		}
+	}
+
+	/// Removes the node from the graph and returns it.
+	/// Nodes still referring to it are left dangling.
+	pub fn remove_node(&mut self, id: Id) -> Option<Node<T>> {
+		self.list_users();
+
+		let node = self.nodes.remove(id)?;
+
+		if let Some(users) = &mut self.users {
+			users.remove_node(id, &node);
+		}
+
+		if self.root == Some(id) {
+			self.root = None;
+		}
+
+		Some(node)
+	}
+
+	/// Removes the nodes of the region of the compound node, including the nodes of their
+	/// nested regions and the [`Argument`] node of the region. The compound node is kept.
+	///
+	/// Only the members found by the [`RegionFinder`] are removed, so the nodes of
+	/// enclosing regions are left as they are.
+	///
+	/// # Panics
+	///
+	/// Panics if the node does not have that region.
+	pub fn remove_subgraph(&mut self, id: Id, region: usize) {
+		let mut region_finder = RegionFinder::new();
+		let mut members = vec![self.nodes[id].as_arguments().unwrap()[region]];
+		let mut regions = vec![(id, region)];
+
+		while let Some((id, region)) = regions.pop() {
+			region_finder.run(self, id, region);
+
+			for &member in region_finder.nodes() {
+				let arguments = self.nodes[member].as_arguments().unwrap_or_default();
+
+				regions.extend((0..arguments.len()).map(|region| (member, region)));
+				members.extend_from_slice(arguments);
+				members.push(member);
+			}
+		}
+
+		for member in members {
+			self.remove_node(member);
+		}
+	}
+
+	/// Replaces the node with a new one and returns the old one.
+	pub fn replace_node(&mut self, id: Id, node: Node<T>) -> Node<T> {
+		self.modify(id, |old| std::mem::replace(old, node))
+	}
+
+	/// Applies the function to the node while keeping its users up to date.
+	pub fn modify<R, F>(&mut self, id: Id, function: F) -> R
+	where
+		F: FnOnce(&mut Node<T>) -> R,
+	{
+		self.list_users();
+
+		let node = &mut self.nodes[id];
+
+		if let Some(users) = &mut self.users {
+			users.remove_node(id, node);
+		}
+
+		let result = function(node);
+
+		if let Some(users) = &mut self.users {
+			users.insert_node(id, node);
+		}
+
+		result
+	}
+
+	fn find_uses(&self, id: Id, link: Link, uses: &mut Vec<Use>) {
+		let node = &self.nodes[id];
+
+		for (index, _) in node.parameters().enumerate().filter(|v| *v.1 == link) {
+			uses.push(Use::Parameter { node: id, index });
+		}
+
+		for (region, list) in node.as_results().unwrap_or_default().iter().enumerate() {
+			for (index, _) in list.iter().enumerate().filter(|v| *v.1 == link) {
+				uses.push(Use::Result {
+					node: id,
+					region,
+					index,
+				});
+			}
+		}
+	}
+
+	/// Removes every node that does not contribute to the roots and returns their [`Id`]s.
+	/// The [`Omega`] node of the graph, if any, is always a root.
+	///
+	/// Results of live regions that are never read, such as those of unused [`Gamma`] outputs,
+	/// are set to [`Link::dangling`] so that the nodes only they refer to can be removed.
+	/// Such results are accepted by the [`Verifier`] and have no tracked users.
+	///
+	/// [`Verifier`]: crate::verify::verifier::Verifier
+	pub fn collect_garbage<I: IntoIterator<Item = Id>>(&mut self, roots: I) -> Vec<Id> {
+		let mut live = Liveness::default();
+
+		live.run(&self.nodes, roots.into_iter().chain(self.root));
+
+		let dead: Vec<_> = self
+			.nodes
+			.iter()
+			.map(|(id, _)| id)
+			.filter(|id| !live.nodes.contains(id))
+			.collect();
+
+		for &id in &dead {
+			self.remove_node(id);
+		}
+
+		let mut unused = Vec::new();
+
+		for &id in &live.nodes {
+			let results = self.nodes[id].as_results().unwrap_or_default();
+
+			for (region, list) in results.iter().enumerate() {
+				for (index, &link) in list.iter().enumerate() {
+					if link != Link::dangling() && !live.results.contains(&(id, region, index)) {
+						unused.push((id, region, index));
+					}
+				}
+			}
+		}
+
+		for (id, region, index) in unused {
+			self.set_result(id, region, index, Link::dangling());
+		}
+
+		dead
+	}
+
+	/// Sets a result of a region of the node and returns the old one.
+	///
+	/// # Panics
+	///
+	/// Panics if the node does not have that result.
+	pub fn set_result(&mut self, id: Id, region: usize, index: usize, link: Link) -> Link {
+		self.list_users();
+
+		let results = self.nodes[id]
+			.as_mut_results()
+			.expect("node should be compound");
+		let old = std::mem::replace(&mut results[region][index], link);
+
+		if let Some(users) = &mut self.users {
+			let user = Use::Result {
+				node: id,
+				region,
+				index,
+			};
+
+			users.remove(old, user);
+			users.insert(link, user);
+		}
+
+		old
+	}
+}
+
+impl<T: Parameters + ParametersMut> DataFlowGraph<T> {
+	/// Sets a parameter of the node and returns the old one.
+	///
+	/// # Panics
+	///
+	/// Panics if the node does not have that parameter.
+	pub fn set_parameter(&mut self, id: Id, index: usize, link: Link) -> Link {
+		self.list_users();
+
+		let parameter = self.nodes[id]
+			.parameters_mut()
+			.nth(index)
+			.expect("node should have parameter");
+		let old = std::mem::replace(parameter, link);
+
+		if let Some(users) = &mut self.users {
+			let user = Use::Parameter { node: id, index };
+
+			users.remove(old, user);
+			users.insert(link, user);
+		}
+
+		old
+	}
+
+	fn set_use(&mut self, user: Use, link: Link) {
+		match user {
+			Use::Parameter { node, index } => {
+				self.set_parameter(node, index, link);
+			}
+			Use::Result {
+				node,
+				region,
+				index,
+			} => {
+				self.set_result(node, region, index, link);
+			}
+		}
+	}
+
+	/// Replaces every use of `from` with `to`, including any uses by `to` itself.
+	/// Returns the number of uses replaced.
+	pub fn replace_uses(&mut self, from: Link, to: Link) -> usize {
+		self.list_users();
+
+		let uses = if let Some(users) = &self.users {
+			users.users_of(from).to_vec()
+		} else {
+			let mut uses = Vec::new();
+
+			for (id, _) in self.nodes.iter() {
+				self.find_uses(id, from, &mut uses);
+			}
+
+			uses
+		};
+
+		for &user in &uses {
+			self.set_use(user, to);
+		}
+
+		uses.len()
+	}
+
+	/// Replaces every use of `from` with `to` inside of the region last found by the [`RegionFinder`].
+	/// Returns the number of uses replaced.
+	pub fn replace_region_uses(&mut self, region: &RegionFinder, from: Link, to: Link) -> usize {
+		let is_inside = |user: &Use| match *user {
+			Use::Parameter { node, .. } => region.contains(node),
+			Use::Result {
+				node,
+				region: index,
+				..
+			} => node == region.parent() && index == region.region(),
+		};
+
+		self.list_users();
+
+		let uses: Vec<_> = if let Some(users) = &self.users {
+			users
+				.users_of(from)
+				.iter()
+				.copied()
+				.filter(|user| is_inside(user))
+				.collect()
+		} else {
+			let mut uses = Vec::new();
+
+			for &id in region.nodes() {
+				self.find_uses(id, from, &mut uses);
+			}
+
+			self.find_uses(region.parent(), from, &mut uses);
+
+			uses.retain(is_inside);
+			uses
+		};
+
+		for &user in &uses {
+			self.set_use(user, to);
+		}
+
+		uses.len()
+	}
+
+	fn remap_nodes(&mut self, map: &HashMap<Id, Id>) {
+		self.list_users();
+
+		for &id in map.values() {
+			let node = &mut self.nodes[id];
+
+			remap_node(node, map);
+
+			if let Some(users) = &mut self.users {
+				users.insert_node(id, node);
+			}
+		}
+	}
+
+	/// Moves every node of the other graph into this one and returns a map
+	/// from their [`Id`]s in the other graph to those in this one.
+	/// The root of the other graph becomes the root of this one if it has none.
+	///
+	/// Links to nodes that no longer exist in the other graph are set to [`Link::dangling`],
+	/// as their [`Id`]s may be given to nodes of this one.
+	pub fn absorb(&mut self, mut other: Self) -> HashMap<Id, Id> {
+		let ids: Vec<_> = other.nodes.iter().map(|(id, _)| id).collect();
+		let mut map = HashMap::with_capacity(ids.len());
+
+		for id in ids {
+			if let Some(node) = other.nodes.remove(id) {
+				map.insert(id, self.nodes.insert(node));
+			}
+		}
+
+		for &id in map.values() {
+			dangle_node(&mut self.nodes[id], &map);
+		}
+
+		self.remap_nodes(&map);
+
+		if self.root.is_none() {
+			self.root = other.root.and_then(|id| map.get(&id).copied());
+		}
+
+		map
+	}
+
+	fn find_topological_order(&self) -> Vec<Id> {
+		let mut searcher = DepthFirstSearcher::new();
+		let mut order = Vec::with_capacity(self.nodes.len());
+
+		let active = searcher.nodes_mut();
+
+		active.clear();
+		active.extend(0..self.nodes.indices_needed());
+
+		let ids = self.nodes.iter().map(|(id, _)| id);
+
+		for id in self.root.into_iter().chain(ids) {
+			searcher.run(self, id, |event| {
+				if let Event::PostNode { id } = event {
+					order.push(id);
+				}
+			});
+		}
+
+		order
+	}
+
+	/// Renumbers the nodes so that their [`Id`]s are dense and returns a map
+	/// from their old [`Id`]s to their new ones. If `topological` is set, every node
+	/// is numbered after its parameters, otherwise the current order is kept.
+	///
+	/// Links to nodes that no longer exist are set to [`Link::dangling`],
+	/// as their [`Id`]s may be given to other nodes.
+	pub fn compact(&mut self, topological: bool) -> HashMap<Id, Id> {
+		let order: Vec<_> = if topological {
+			self.find_topological_order()
+		} else {
+			self.nodes.iter().map(|(id, _)| id).collect()
+		};
+
+		let mut nodes = Arena::with_capacity(order.len());
+		let mut map = HashMap::with_capacity(order.len());
+
+		for id in order {
+			let node = self.nodes.remove(id).unwrap();
+
+			map.insert(id, nodes.insert(node));
+		}
+
+		for (_, node) in nodes.iter_mut() {
+			dangle_node(node, &map);
+			remap_node(node, &map);
+		}
+
+		self.nodes = nodes;
+		self.root = self.root.and_then(|id| map.get(&id).copied());
+
+		if self.users.is_some() {
+			self.track_users();
+		}
+
+		map
+	}
+
+	/// Passes the link into every region of the compound node as a new parameter
+	/// and returns the port of the [`Argument`] nodes where it is available.
+	///
+	/// # Panics
+	///
+	/// Panics if the node is not compound or is an [`Omega`] node.
+	pub fn add_parameter(&mut self, id: Id, link: Link) -> u16 {
+		let node = &self.nodes[id];
+		let (index, shifted) = match node {
+			Node::Gamma(gamma) => (gamma.parameters.len().saturating_sub(1), 0),
+			Node::Theta(theta) => (theta.parameters.len(), 0),
+			Node::Phi(phi) => (phi.parameters.len(), phi.results.len()),
+			Node::Lambda(lambda) => (lambda.parameters.len(), lambda.inputs),
+			Node::Delta(delta) => (delta.parameters.len(), 0),
+			Node::Simple(_) | Node::Argument(_) | Node::Omega(_) => {
+				panic!("node should take parameters")
+			}
+		};
+
+		let arguments = node.as_arguments().unwrap_or_default().to_vec();
+		let port: u16 = index.try_into().unwrap();
+		let shifted: u16 = shifted.try_into().unwrap();
+
+		// Ports after the bound parameters move up to make room.
+		for node in arguments {
+			for offset in (0..shifted).rev() {
+				let from = Link {
+					node,
+					port: port + offset,
+				};
+				let to = Link {
+					node,
+					port: port + offset + 1,
+				};
+
+				self.replace_uses(from, to);
+			}
+		}
+
+		self.modify(id, |node| match node {
+			Node::Gamma(gamma) => gamma.parameters.insert(index, link),
+			Node::Theta(theta) => {
+				let node = theta.argument;
+
+				theta.parameters.push(link);
+				theta.results.insert(index, Link { node, port });
+			}
+			Node::Phi(phi) => phi.parameters.push(link),
+			Node::Lambda(lambda) => lambda.parameters.push(link),
+			Node::Delta(delta) => delta.parameters.push(link),
+			Node::Simple(_) | Node::Argument(_) | Node::Omega(_) => unreachable!(),
+		});
+
+		port
+	}
+}
+
+impl<T: Clone + Parameters + ParametersMut> DataFlowGraph<T> {
+	/// Copies the nodes of the region of the compound node, including the nodes of their
+	/// nested regions, and returns a map from the original [`Id`]s to those of their copies.
+	///
+	/// Only the members found by the [`RegionFinder`] are copied, so copies keep referring
+	/// to the nodes of enclosing regions. The [`Argument`] node of the region is copied
+	/// without a parent, so its uses can be redirected before it is removed or given to a new node.
+	///
+	/// # Panics
+	///
+	/// Panics if the node does not exist.
+	pub fn clone_subgraph(&mut self, id: Id, region: usize) -> HashMap<Id, Id> {
+		let mut region_finder = RegionFinder::new();
+		let mut map = HashMap::new();
+		let mut regions = vec![(id, region)];
+
+		while let Some((id, region)) = regions.pop() {
+			region_finder.run(self, id, region);
+
+			for &member in region_finder.nodes() {
+				if map.contains_key(&member) {
+					continue;
+				}
+
+				let node = self.nodes[member].clone();
+				let arguments = node.as_arguments().unwrap_or_default();
+
+				regions.extend((0..arguments.len()).map(|region| (member, region)));
+
+				for &argument in arguments {
+					let copy = self.nodes[argument].clone();
+
+					map.insert(argument, self.nodes.insert(copy));
+				}
+
+				map.insert(member, self.nodes.insert(node));
+			}
+		}
+
+		self.remap_nodes(&map);
+
+		map
+	}
+
+	/// Copies the region of the compound node with the links passed in place of
+	/// its [`Argument`] node and returns the results of the copy.
+	///
+	/// # Panics
+	///
+	/// Panics if the node does not have that region, or if there is not exactly one input
+	/// for every port of its [`Argument`] node.
+	pub fn clone_region(&mut self, id: Id, region: usize, inputs: &[Link]) -> Vec<Link> {
+		let node = &self.nodes[id];
+		let argument = node.as_arguments().unwrap()[region];
+		let results = node.as_results().unwrap()[region].clone();
+
+		assert_eq!(
+			inputs.len(),
+			node.argument_arity().unwrap_or_default(),
+			"region should take one input per port"
+		);
+		let map = self.clone_subgraph(id, region);
+
+		let copy = map.get(&argument).copied();
+		let remap = |link: Link| match map.get(&link.node) {
+			Some(&node) if Some(node) == copy => inputs[usize::from(link.port)],
+			Some(&node) => Link {
+				node,
+				port: link.port,
+			},
+			None => link,
+		};
+
+		let results = results.into_iter().map(remap).collect();
+
+		if let Some(copy) = copy {
+			for (port, &link) in inputs.iter().enumerate() {
+				let from = Link {
+					node: copy,
+					port: port.try_into().unwrap(),
+				};
+
+				self.replace_uses(from, link);
+			}
+
+			self.remove_node(copy);
+		}
+
+		results
 	}
 }
 
@@ -123,3 +1071,316 @@ impl<T> std::ops::DerefMut for DataFlowGraph<T> {
 		self.nodes_mut()
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use arena::referent::Referent;
+
+	use crate::{
+		collection::link::Link,
+		fixture::Simple,
+		verify::{diagnostic::Diagnostic, verifier::Verifier},
+		visit::region_finder::RegionFinder,
+	};
+
+	use super::DataFlowGraph;
+
+	fn test_replace_uses(track: bool) {
+		let mut nodes = DataFlowGraph::<Simple>::new();
+
+		if track {
+			nodes.track_users();
+		}
+
+		let node_0 = nodes.add_simple(Simple(Vec::new()));
+		let node_1 = nodes.add_simple(Simple(Vec::new()));
+		let node_2 = nodes.add_simple(Simple(vec![node_0, node_0]));
+		let argument = nodes.add_argument();
+		let node_3 = nodes.add_simple(Simple(vec![node_0, argument]));
+		let node_5 = nodes.add_simple(Simple(vec![node_2, argument]));
+		let node_4 = nodes.add_lambda(vec![node_0], 0, argument.node, vec![node_3, node_0, node_5]);
+		let _node_6 = nodes.add_simple(Simple(vec![node_2]));
+
+		let mut region = RegionFinder::new();
+
+		region.run(&nodes, node_4.node, 0);
+
+		// Nodes of the enclosing region read directly are not members.
+		assert!(region.contains(node_3.node));
+		assert!(!region.contains(node_0.node));
+		assert!(!region.contains(node_2.node));
+
+		assert_eq!(nodes.replace_region_uses(&region, node_0, node_1), 2);
+		assert_eq!(nodes[node_2.node].parameters().count(), 2);
+		assert!(nodes[node_2.node].parameters().all(|&link| link == node_0));
+		assert_eq!(nodes[node_4.node].as_parameters(), Some(&[node_0][..]));
+
+		assert_eq!(nodes.replace_uses(node_0, node_1), 3);
+		assert!(nodes[node_2.node].parameters().all(|&link| link == node_1));
+		assert_eq!(nodes[node_4.node].as_parameters(), Some(&[node_1][..]));
+
+		if track {
+			assert!(nodes.users_of(node_0).is_empty());
+			assert_eq!(nodes.users_of(node_1).len(), 5);
+		}
+	}
+
+	#[test]
+	fn test_collect_garbage() {
+		let mut nodes = DataFlowGraph::<Simple>::new();
+
+		let node_0 = nodes.add_simple(Simple(Vec::new()));
+		let node_1 = nodes.add_simple(Simple(Vec::new()));
+		let node_2 = nodes.add_simple(Simple(vec![node_1]));
+		let node_3 = nodes.add_simple(Simple(Vec::new()));
+		let arguments = [nodes.add_argument().node, nodes.add_argument().node];
+		let node_4 = nodes.add_gamma(
+			vec![node_3],
+			arguments.into_iter().collect(),
+			[vec![node_0, node_2], vec![node_0, node_1]]
+				.into_iter()
+				.collect(),
+		);
+		let node_5 = nodes.add_simple(Simple(vec![node_4]));
+		let node_6 = nodes.add_simple(Simple(vec![node_0]));
+
+		let mut dead = nodes.collect_garbage([node_5.node]);
+
+		dead.sort_unstable();
+
+		let mut expected = [node_1.node, node_2.node, node_6.node];
+
+		expected.sort_unstable();
+
+		assert_eq!(dead, expected);
+
+		for results in nodes[node_4.node].as_results().unwrap() {
+			assert_eq!(results, &[node_0, Link::dangling()]);
+		}
+
+		// Searches skip over the results left dangling.
+		RegionFinder::new().run(&nodes, node_4.node, 1);
+
+		assert_eq!(nodes.compact(true).len(), 6);
+	}
+
+	#[test]
+	fn test_collect_garbage_keeps_root() {
+		let mut nodes = DataFlowGraph::<Simple>::new();
+
+		let argument = nodes.add_argument();
+		let node_0 = nodes.add_simple(Simple(vec![argument]));
+		let node_1 = nodes.add_simple(Simple(Vec::new()));
+		let omega = nodes.add_omega(
+			vec!["input".into()],
+			argument.node,
+			vec!["output".into()],
+			vec![node_0],
+		);
+
+		assert_eq!(nodes.root(), Some(omega));
+		assert_eq!(nodes.collect_garbage([]), [node_1.node]);
+
+		let _ = nodes.remove_node(omega);
+
+		assert_eq!(nodes.root(), None);
+	}
+
+	#[test]
+	fn test_clone_subgraph() {
+		let mut nodes = DataFlowGraph::<Simple>::new();
+
+		let outer = nodes.add_simple(Simple(Vec::new()));
+		let argument = nodes.add_argument();
+		let arguments = [nodes.add_argument().node, nodes.add_argument().node];
+		let inner = nodes.add_simple(Simple(vec![arguments[0].into(), outer]));
+		let gamma = nodes.add_gamma(
+			vec![argument, argument],
+			arguments.into_iter().collect(),
+			[vec![inner], vec![arguments[1].into()]]
+				.into_iter()
+				.collect(),
+		);
+		let body = nodes.add_simple(Simple(vec![gamma, outer]));
+		let lambda = nodes.add_lambda(Vec::new(), 1, argument.node, vec![body]);
+		let _user = nodes.add_simple(Simple(vec![outer]));
+
+		let map = nodes.clone_subgraph(lambda.node, 0);
+		let mut cloned: Vec<_> = map.keys().copied().collect();
+		let mut expected = [
+			argument.node,
+			arguments[0],
+			arguments[1],
+			inner.node,
+			gamma.node,
+			body.node,
+		];
+
+		cloned.sort_unstable();
+		expected.sort_unstable();
+
+		assert_eq!(cloned, expected);
+
+		let copy = Link {
+			node: map[&body.node],
+			port: 0,
+		};
+		let gamma = Link {
+			node: map[&gamma.node],
+			port: 0,
+		};
+
+		let _ = nodes.add_lambda(Vec::new(), 1, map[&argument.node], vec![copy]);
+
+		assert_eq!(nodes[copy.node].as_simple().unwrap().0, [gamma, outer]);
+		assert_eq!(nodes[map[&inner.node]].as_simple().unwrap().0[1], outer);
+		assert!(Verifier::new().run(&nodes).is_empty());
+	}
+
+	#[test]
+	fn test_absorb() {
+		let mut nodes = DataFlowGraph::<Simple>::new();
+		let mut other = DataFlowGraph::<Simple>::new();
+
+		let _ = nodes.add_simple(Simple(Vec::new()));
+
+		let node_0 = other.add_simple(Simple(Vec::new()));
+		let node_1 = other.add_simple(Simple(Vec::new()));
+		let argument = other.add_argument();
+		let arguments = [other.add_argument().node, other.add_argument().node];
+		let node_2 = other.add_simple(Simple(vec![arguments[0].into(), node_0]));
+		let node_3 = other.add_gamma(
+			vec![argument, argument],
+			arguments.into_iter().collect(),
+			[vec![node_2], vec![arguments[1].into()]]
+				.into_iter()
+				.collect(),
+		);
+		let node_4 = other.add_simple(Simple(vec![node_3, node_1]));
+		let node_5 = other.add_lambda(vec![node_0], 0, argument.node, vec![node_4]);
+
+		let _ = other.remove_node(node_1.node);
+
+		let map = nodes.absorb(other);
+		let find = |link: Link| Link {
+			node: map[&link.node],
+			port: link.port,
+		};
+
+		assert_eq!(map.len(), 8);
+		assert_eq!(
+			nodes[map[&node_4.node]].as_simple().unwrap().0,
+			[find(node_3), Link::dangling()]
+		);
+
+		let lambda = nodes[map[&node_5.node]].as_lambda().unwrap();
+
+		assert_eq!(lambda.parameters, [find(node_0)]);
+		assert_eq!(lambda.argument, map[&argument.node]);
+		assert_eq!(
+			nodes[map[&arguments[0]]].as_argument().unwrap().parent,
+			map[&node_3.node]
+		);
+		assert_eq!(
+			Verifier::new().run(&nodes),
+			[Diagnostic::DanglingLink {
+				user: map[&node_4.node],
+				link: Link::dangling(),
+			}]
+		);
+	}
+
+	#[test]
+	fn test_compact() {
+		let mut nodes = DataFlowGraph::<Simple>::new();
+
+		let node_0 = nodes.add_simple(Simple(Vec::new()));
+		let node_1 = nodes.add_simple(Simple(Vec::new()));
+		let node_2 = nodes.add_simple(Simple(vec![node_0]));
+		let node_3 = nodes.add_simple(Simple(vec![node_1]));
+
+		let _ = nodes.remove_node(node_0.node);
+		let node_4 = nodes.add_simple(Simple(vec![node_3]));
+		let _ = nodes.remove_node(node_1.node);
+		let map = nodes.compact(true);
+
+		assert_eq!(nodes.len(), 3);
+		assert_eq!(nodes.indices_needed(), 3);
+		assert_eq!(
+			nodes[map[&node_2.node]].as_simple().unwrap().0,
+			[Link::dangling()]
+		);
+		assert_eq!(
+			nodes[map[&node_4.node]].as_simple().unwrap().0[0].node,
+			map[&node_3.node]
+		);
+
+		for (id, node) in nodes.iter() {
+			for link in node.parameters() {
+				assert!(nodes.get(link.node).is_none() || link.node.index() < id.index());
+			}
+		}
+	}
+
+	#[test]
+	fn test_add_simple_without_parameters() {
+		let mut nodes = DataFlowGraph::<u8>::new();
+
+		let node_0 = nodes.add_simple(1);
+
+		assert_eq!(nodes[node_0.node].as_simple(), Some(&1));
+	}
+
+	#[test]
+	fn test_adopts_arguments() {
+		let mut nodes = DataFlowGraph::<Simple>::new();
+
+		let argument_0 = nodes.add_argument();
+		let argument_1 = nodes.add_argument();
+		let predicate = nodes.add_simple(Simple(Vec::new()));
+		let gamma = nodes.add_gamma(
+			vec![predicate],
+			[argument_0.node, argument_1.node].into_iter().collect(),
+			[Vec::new(), Vec::new()].into_iter().collect(),
+		);
+
+		for (region, argument) in [argument_0, argument_1].into_iter().enumerate() {
+			let argument = nodes[argument.node].as_argument().unwrap();
+
+			assert_eq!(argument.parent, gamma.node);
+			assert_eq!(argument.region, region);
+		}
+
+		assert!(Verifier::new().run(&nodes).is_empty());
+	}
+
+	#[test]
+	#[should_panic = "region start should be an `Argument` of the graph"]
+	fn test_rejects_non_argument_region() {
+		let mut nodes = DataFlowGraph::<Simple>::new();
+
+		let node_0 = nodes.add_simple(Simple(Vec::new()));
+		let _ = nodes.add_theta(vec![node_0], node_0.node, vec![node_0, node_0]);
+	}
+
+	#[test]
+	#[should_panic = "region should take one input per port"]
+	fn test_rejects_missing_region_inputs() {
+		let mut nodes = DataFlowGraph::<Simple>::new();
+
+		let argument = nodes.add_argument();
+		let lambda = nodes.add_lambda(Vec::new(), 1, argument.node, vec![argument]);
+
+		let _ = nodes.clone_region(lambda.node, 0, &[]);
+	}
+
+	#[test]
+	fn test_replace_uses_untracked() {
+		test_replace_uses(false);
+	}
+
+	#[test]
+	fn test_replace_uses_tracked() {
+		test_replace_uses(true);
+	}
+}
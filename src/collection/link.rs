@@ -22,6 +22,9 @@ impl Link {
 		}
 	}
 
+	/// Returns an iterator over every port from this one up to `u16::MAX`, which is
+	/// far past the outputs of any real node.
+	#[deprecated = "use `DataFlowGraph::outputs`, which stops at the arity of the node"]
 	#[inline]
 	#[must_use]
 	pub const fn iter(self) -> Iter {
@@ -45,16 +48,6 @@ impl From<Id> for Link {
 	}
 }
 
-impl IntoIterator for Link {
-	type Item = Self;
-	type IntoIter = Iter;
-
-	#[inline]
-	fn into_iter(self) -> Self::IntoIter {
-		self.iter()
-	}
-}
-
 #[derive(Clone, Debug, Default, Hash, PartialEq, Eq)]
 pub struct Iter {
 	pub node: Id,
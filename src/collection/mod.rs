@@ -1,3 +1,8 @@
+pub mod builder;
 pub mod data_flow_graph;
 pub mod link;
 pub mod node;
+pub mod users;
+
+#[cfg(feature = "serde")]
+mod serial;
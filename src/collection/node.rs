@@ -1,6 +1,6 @@
 use list::resizable::Resizable;
 
-use super::link::Link;
+use super::link::{Id, Link};
 
 macro_rules! impl_mirrored {
 	($item:expr, $iter:pat => $apply:expr) => {
@@ -134,12 +134,58 @@ macro_rules! impl_compound {
 	};
 }
 
+/// The region "start" node.
+///
+/// The node's results are the values passed into the region `region` of its `parent` node.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Argument {
+	#[cfg_attr(feature = "serde", serde(with = "super::serial::id"))]
+	pub parent: Id,
+	pub region: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NotArgumentError;
+
+impl std::error::Error for NotArgumentError {}
+
+impl std::fmt::Display for NotArgumentError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		std::fmt::Debug::fmt(self, f)
+	}
+}
+
+impl<T> TryFrom<Node<T>> for Argument {
+	type Error = NotArgumentError;
+
+	#[inline]
+	fn try_from(node: Node<T>) -> Result<Self, Self::Error> {
+		match node {
+			Node::Argument(argument) => Ok(argument),
+			_ => Err(NotArgumentError),
+		}
+	}
+}
+
+impl<T> From<Argument> for Node<T> {
+	#[inline]
+	fn from(argument: Argument) -> Self {
+		Self::Argument(argument)
+	}
+}
+
 /// The "select" node.
 ///
 /// The node's last parameter denotes which region to select.
-/// All other parameters are passed into the user-defined start node.
+/// All other parameters are passed into the [`Argument`] node of the selected region.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Gamma {
 	pub parameters: Vec<Link>,
+	#[cfg_attr(feature = "serde", serde(with = "super::serial::ids"))]
+	pub arguments: Resizable<Id, 2>,
+	#[cfg_attr(feature = "serde", serde(with = "super::serial::resizable"))]
 	pub results: Resizable<Vec<Link>, 2>,
 }
 
@@ -148,41 +194,105 @@ impl_compound!(Gamma, NotGammaError);
 /// The "repeat" node.
 ///
 /// The node's last result denotes whether to repeat the loop.
-/// All other parameters and results are passed into the user-defined start node.
+/// All other parameters and results are passed into the [`Argument`] node of the region.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Theta {
 	pub parameters: Vec<Link>,
+	#[cfg_attr(feature = "serde", serde(with = "super::serial::id"))]
+	pub argument: Id,
 	pub results: Vec<Link>,
 }
 
 impl_compound!(Theta, NotThetaError);
 
+impl Theta {
+	/// Returns whether the loop variable at the port is passed back unchanged,
+	/// keeping the same value for every iteration.
+	#[inline]
+	#[must_use]
+	pub fn is_invariant(&self, port: usize) -> bool {
+		let is_passed = |link: &Link| link.node == self.argument && usize::from(link.port) == port;
+
+		port < self.parameters.len() && self.results.get(port).is_some_and(is_passed)
+	}
+}
+
 /// The "function" node.
 ///
 /// The node's parameters are the function's bound inputs.
+/// They are passed into the [`Argument`] node of the region, followed by the `inputs` it is called with.
 /// The node's single result is the function itself.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Lambda {
 	pub parameters: Vec<Link>,
+	pub inputs: usize,
+	#[cfg_attr(feature = "serde", serde(with = "super::serial::id"))]
+	pub argument: Id,
 	pub results: Vec<Link>,
 }
 
 impl_compound!(Lambda, NotLambdaError);
 
+/// The "global" node.
+///
+/// The node's parameters are the context the global is defined in.
+/// They are passed into the [`Argument`] node of the region.
+/// The node's single result is the global itself, whose value is the single result of the region.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Delta {
+	pub parameters: Vec<Link>,
+	#[cfg_attr(feature = "serde", serde(with = "super::serial::id"))]
+	pub argument: Id,
+	pub results: Vec<Link>,
+}
+
+impl_compound!(Delta, NotDeltaError);
+
+/// The "module" node.
+///
+/// The node is the root of the graph and has no parameters or results of its own.
+/// The `imports` are passed into the [`Argument`] node of the region by name,
+/// and the results of the region are exported under the `exports` names.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Omega {
+	pub imports: Vec<String>,
+	#[cfg_attr(feature = "serde", serde(with = "super::serial::id"))]
+	pub argument: Id,
+	pub exports: Vec<String>,
+	pub results: Vec<Link>,
+}
+
+impl_compound!(Omega, NotOmegaError);
+
 /// The "mutually recursive" node.
 ///
-/// All parameters and results are passed into the user-defined start node.
+/// All parameters are passed into the [`Argument`] node of the region, followed by all results.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Phi {
 	pub parameters: Vec<Link>,
+	#[cfg_attr(feature = "serde", serde(with = "super::serial::id"))]
+	pub argument: Id,
 	pub results: Vec<Link>,
 }
 
 impl_compound!(Phi, NotPhiError);
 
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Node<T> {
 	Simple(T),
+	Argument(Argument),
 	Gamma(Gamma),
 	Theta(Theta),
 	Phi(Phi),
 	Lambda(Lambda),
+	Delta(Delta),
+	Omega(Omega),
 }
 
 impl<T> Node<T> {
@@ -206,6 +316,26 @@ impl<T> Node<T> {
 		}
 	}
 
+	/// Returns the node as an [`Argument`] reference if it is one.
+	#[inline]
+	#[must_use]
+	pub const fn as_argument(&self) -> Option<&Argument> {
+		match self {
+			Self::Argument(node) => Some(node),
+			_ => None,
+		}
+	}
+
+	/// Returns the node as an [`Argument`] mutable reference if it is one.
+	#[inline]
+	#[must_use]
+	pub fn as_mut_argument(&mut self) -> Option<&mut Argument> {
+		match self {
+			Self::Argument(node) => Some(node),
+			_ => None,
+		}
+	}
+
 	/// Returns the node as a [`Gamma`] reference if it is one.
 	#[inline]
 	#[must_use]
@@ -286,16 +416,92 @@ impl<T> Node<T> {
 		}
 	}
 
+	/// Returns the node as a [`Delta`] reference if it is one.
+	#[inline]
+	#[must_use]
+	pub const fn as_delta(&self) -> Option<&Delta> {
+		match self {
+			Self::Delta(node) => Some(node),
+			_ => None,
+		}
+	}
+
+	/// Returns the node as a [`Delta`] mutable reference if it is one.
+	#[inline]
+	#[must_use]
+	pub fn as_mut_delta(&mut self) -> Option<&mut Delta> {
+		match self {
+			Self::Delta(node) => Some(node),
+			_ => None,
+		}
+	}
+
+	/// Returns the node as an [`Omega`] reference if it is one.
+	#[inline]
+	#[must_use]
+	pub const fn as_omega(&self) -> Option<&Omega> {
+		match self {
+			Self::Omega(node) => Some(node),
+			_ => None,
+		}
+	}
+
+	/// Returns the node as an [`Omega`] mutable reference if it is one.
+	#[inline]
+	#[must_use]
+	pub fn as_mut_omega(&mut self) -> Option<&mut Omega> {
+		match self {
+			Self::Omega(node) => Some(node),
+			_ => None,
+		}
+	}
+
+	/// Returns a reference to the [`Argument`] nodes of each region if it is compound.
+	#[inline]
+	#[must_use]
+	pub fn as_arguments(&self) -> Option<&[Id]> {
+		let result = match self {
+			Self::Simple(_) | Self::Argument(_) => return None,
+			Self::Gamma(node) => &node.arguments,
+			Self::Theta(node) => std::slice::from_ref(&node.argument),
+			Self::Phi(node) => std::slice::from_ref(&node.argument),
+			Self::Lambda(node) => std::slice::from_ref(&node.argument),
+			Self::Delta(node) => std::slice::from_ref(&node.argument),
+			Self::Omega(node) => std::slice::from_ref(&node.argument),
+		};
+
+		Some(result)
+	}
+
+	/// Returns a mutable reference to the [`Argument`] nodes of each region if it is compound.
+	#[inline]
+	#[must_use]
+	pub fn as_mut_arguments(&mut self) -> Option<&mut [Id]> {
+		let result = match self {
+			Self::Simple(_) | Self::Argument(_) => return None,
+			Self::Gamma(node) => &mut node.arguments,
+			Self::Theta(node) => std::slice::from_mut(&mut node.argument),
+			Self::Phi(node) => std::slice::from_mut(&mut node.argument),
+			Self::Lambda(node) => std::slice::from_mut(&mut node.argument),
+			Self::Delta(node) => std::slice::from_mut(&mut node.argument),
+			Self::Omega(node) => std::slice::from_mut(&mut node.argument),
+		};
+
+		Some(result)
+	}
+
 	/// Returns a reference to the results arrays of the node if it is compound.
 	#[inline]
 	#[must_use]
 	pub fn as_results(&self) -> Option<&[Vec<Link>]> {
 		let result = match self {
-			Self::Simple(_) => return None,
+			Self::Simple(_) | Self::Argument(_) => return None,
 			Self::Gamma(node) => &node.results,
 			Self::Theta(node) => std::slice::from_ref(&node.results),
 			Self::Phi(node) => std::slice::from_ref(&node.results),
 			Self::Lambda(node) => std::slice::from_ref(&node.results),
+			Self::Delta(node) => std::slice::from_ref(&node.results),
+			Self::Omega(node) => std::slice::from_ref(&node.results),
 		};
 
 		Some(result)
@@ -306,45 +512,94 @@ impl<T> Node<T> {
 	#[must_use]
 	pub fn as_mut_results(&mut self) -> Option<&mut [Vec<Link>]> {
 		let result = match self {
-			Self::Simple(_) => return None,
+			Self::Simple(_) | Self::Argument(_) => return None,
 			Self::Gamma(node) => &mut node.results,
 			Self::Theta(node) => std::slice::from_mut(&mut node.results),
 			Self::Phi(node) => std::slice::from_mut(&mut node.results),
 			Self::Lambda(node) => std::slice::from_mut(&mut node.results),
+			Self::Delta(node) => std::slice::from_mut(&mut node.results),
+			Self::Omega(node) => std::slice::from_mut(&mut node.results),
 		};
 
 		Some(result)
 	}
 
-	/// Returns a reference to the parameters of the node if it is compound.
+	/// Returns a reference to the parameters of the node if it is compound and not [`Omega`].
 	#[inline]
 	#[must_use]
 	pub fn as_parameters(&self) -> Option<&[Link]> {
 		let result = match self {
-			Self::Simple(_) => return None,
+			Self::Simple(_) | Self::Argument(_) | Self::Omega(_) => return None,
 			Self::Gamma(node) => &node.parameters,
 			Self::Theta(node) => &node.parameters,
 			Self::Phi(node) => &node.parameters,
 			Self::Lambda(node) => &node.parameters,
+			Self::Delta(node) => &node.parameters,
 		};
 
 		Some(result)
 	}
 
-	/// Returns a mutable reference to the parameters of the node if it is compound.
+	/// Returns a mutable reference to the parameters of the node if it is compound and not [`Omega`].
 	#[inline]
 	#[must_use]
 	pub fn as_mut_parameters(&mut self) -> Option<&mut Vec<Link>> {
 		let result = match self {
-			Self::Simple(_) => return None,
+			Self::Simple(_) | Self::Argument(_) | Self::Omega(_) => return None,
 			Self::Gamma(node) => &mut node.parameters,
 			Self::Theta(node) => &mut node.parameters,
 			Self::Phi(node) => &mut node.parameters,
 			Self::Lambda(node) => &mut node.parameters,
+			Self::Delta(node) => &mut node.parameters,
 		};
 
 		Some(result)
 	}
+
+	/// Returns the number of results of the [`Argument`] nodes of the node's regions,
+	/// if it is a compound node.
+	#[inline]
+	#[must_use]
+	pub fn argument_arity(&self) -> Option<usize> {
+		let arity = match self {
+			Self::Simple(_) | Self::Argument(_) => return None,
+			Self::Gamma(node) => node.parameters.len().saturating_sub(1),
+			Self::Theta(node) => node.parameters.len(),
+			Self::Phi(node) => node.parameters.len() + node.results.len(),
+			Self::Lambda(node) => node.parameters.len() + node.inputs,
+			Self::Delta(node) => node.parameters.len(),
+			Self::Omega(node) => node.imports.len(),
+		};
+
+		Some(arity)
+	}
+}
+
+/// A node that knows how many results it has.
+pub trait Arity {
+	/// Returns the number of results of the node.
+	#[must_use]
+	fn arity(&self) -> usize;
+}
+
+impl<T: Arity> Node<T> {
+	/// Returns the number of results of the node, derived from its regions for compound nodes.
+	/// Returns `None` for [`Argument`] nodes, as their results are given by their parent.
+	#[inline]
+	#[must_use]
+	pub fn arity(&self) -> Option<usize> {
+		let arity = match self {
+			Self::Simple(node) => node.arity(),
+			Self::Argument(_) => return None,
+			Self::Gamma(node) => node.results.first().map_or(0, Vec::len),
+			Self::Theta(node) => node.results.len().saturating_sub(1),
+			Self::Phi(node) => node.results.len(),
+			Self::Lambda(_) | Self::Delta(_) => 1,
+			Self::Omega(_) => 0,
+		};
+
+		Some(arity)
+	}
 }
 
 /// A node that can represent parameters as an iterator of [`Link`] referencess.
@@ -372,10 +627,12 @@ impl<T: Parameters> Node<T> {
 	pub fn parameters(&self) -> Iter<'_, T::Iter<'_>> {
 		let iter = match self {
 			Self::Simple(node) => return Iter::Simple(node.parameters()),
+			Self::Argument(_) | Self::Omega(_) => Default::default(),
 			Self::Gamma(node) => node.parameters.iter(),
 			Self::Theta(node) => node.parameters.iter(),
 			Self::Phi(node) => node.parameters.iter(),
 			Self::Lambda(node) => node.parameters.iter(),
+			Self::Delta(node) => node.parameters.iter(),
 		};
 
 		Iter::List(iter)
@@ -407,10 +664,12 @@ impl<T: ParametersMut> Node<T> {
 	pub fn parameters_mut(&mut self) -> IterMut<'_, T::IterMut<'_>> {
 		let iter = match self {
 			Self::Simple(node) => return IterMut::Simple(node.parameters_mut()),
+			Self::Argument(_) | Self::Omega(_) => Default::default(),
 			Self::Gamma(node) => node.parameters.iter_mut(),
 			Self::Theta(node) => node.parameters.iter_mut(),
 			Self::Phi(node) => node.parameters.iter_mut(),
 			Self::Lambda(node) => node.parameters.iter_mut(),
+			Self::Delta(node) => node.parameters.iter_mut(),
 		};
 
 		IterMut::List(iter)
@@ -0,0 +1,887 @@
+use std::{cell::RefCell, fmt, marker::PhantomData, thread::LocalKey};
+
+use arena::{
+	collection::Arena,
+	referent::{Referent, Similar},
+};
+use serde::{
+	de::{
+		value::SeqDeserializer, DeserializeSeed, EnumAccess, Error as _, IntoDeserializer,
+		MapAccess, SeqAccess, VariantAccess, Visitor,
+	},
+	ser::{Error as _, SerializeStruct},
+	Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use super::{
+	data_flow_graph::DataFlowGraph,
+	link::{Id, Link},
+	node::{Node, ParametersMut},
+};
+
+// Nodes are referred to by their index in the arena alone, which keeps the format
+// independent of the generations. A graph is written with a slot for every index,
+// so that every node keeps its index when read back.
+const DANGLING: u32 = u32::MAX;
+
+// Names that mark ids and links to the reader of a graph, which resolves them
+// only once all of its nodes are read.
+const ID: &str = "$regioned::Id";
+const LINK: &str = "$regioned::Link";
+
+thread_local! {
+	static LIVE: RefCell<Option<Vec<Id>>> = const { RefCell::new(None) };
+}
+
+struct Scope<V: 'static> {
+	key: &'static LocalKey<RefCell<Option<V>>>,
+	previous: Option<V>,
+}
+
+impl<V> Scope<V> {
+	fn enter(key: &'static LocalKey<RefCell<Option<V>>>, value: V) -> Self {
+		let previous = key.replace(Some(value));
+
+		Self { key, previous }
+	}
+}
+
+impl<V> Drop for Scope<V> {
+	fn drop(&mut self) {
+		self.key.set(self.previous.take());
+	}
+}
+
+fn find_index(id: Id) -> u32 {
+	let index: usize = id.index().try_into_unchecked();
+
+	// Inside of a graph, links to nodes not in it are written as dangling.
+	let is_live = LIVE.with_borrow(|live| {
+		live.as_ref()
+			.map_or(id != Id::dangling(), |live| live.get(index) == Some(&id))
+	});
+
+	if is_live {
+		u32::try_from(index).unwrap_or(DANGLING)
+	} else {
+		DANGLING
+	}
+}
+
+// Outside of a graph, the index is given the `Id` a fresh arena gives it,
+// which is the same for every graph read back.
+fn find_fresh(index: u32) -> Id {
+	if index == DANGLING {
+		return Id::dangling();
+	}
+
+	let mut arena = Arena::new();
+
+	loop {
+		let id: Id = arena.insert(());
+
+		if id.index() == index {
+			break id;
+		}
+	}
+}
+
+struct SerialId(Id);
+
+impl Serialize for SerialId {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.serialize_newtype_struct(ID, &find_index(self.0))
+	}
+}
+
+impl<'de> Deserialize<'de> for SerialId {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		let RawId(index) = RawId::deserialize(deserializer)?;
+
+		Ok(Self(find_fresh(index)))
+	}
+}
+
+struct RawId(u32);
+
+impl<'de> Deserialize<'de> for RawId {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		struct IndexVisitor;
+
+		impl<'de> Visitor<'de> for IndexVisitor {
+			type Value = u32;
+
+			fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+				formatter.write_str("a node index")
+			}
+
+			fn visit_u64<E: serde::de::Error>(self, value: u64) -> Result<Self::Value, E> {
+				u32::try_from(value)
+					.map_err(|_| E::custom(format_args!("node index {value} is out of range")))
+			}
+
+			fn visit_newtype_struct<D: Deserializer<'de>>(
+				self,
+				deserializer: D,
+			) -> Result<Self::Value, D::Error> {
+				u32::deserialize(deserializer)
+			}
+		}
+
+		deserializer
+			.deserialize_newtype_struct(ID, IndexVisitor)
+			.map(Self)
+	}
+}
+
+struct RawLink(u32, u16);
+
+impl<'de> Deserialize<'de> for RawLink {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		struct LinkVisitor;
+
+		impl<'de> Visitor<'de> for LinkVisitor {
+			type Value = RawLink;
+
+			fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+				formatter.write_str("a node index and port")
+			}
+
+			fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+				let RawId(index) = seq
+					.next_element()?
+					.ok_or_else(|| A::Error::invalid_length(0, &self))?;
+				let port = seq
+					.next_element()?
+					.ok_or_else(|| A::Error::invalid_length(1, &self))?;
+
+				Ok(RawLink(index, port))
+			}
+
+			fn visit_newtype_struct<D: Deserializer<'de>>(
+				self,
+				deserializer: D,
+			) -> Result<Self::Value, D::Error> {
+				deserializer.deserialize_tuple(2, self)
+			}
+		}
+
+		deserializer.deserialize_newtype_struct(LINK, LinkVisitor)
+	}
+}
+
+pub mod id {
+	use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+	use super::{Id, SerialId};
+
+	pub fn serialize<S: Serializer>(id: &Id, serializer: S) -> Result<S::Ok, S::Error> {
+		SerialId(*id).serialize(serializer)
+	}
+
+	pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Id, D::Error> {
+		SerialId::deserialize(deserializer).map(|id| id.0)
+	}
+}
+
+pub mod ids {
+	use list::resizable::Resizable;
+	use serde::{Deserialize, Deserializer, Serializer};
+
+	use super::{Id, SerialId};
+
+	pub fn serialize<S, const N: usize>(
+		ids: &Resizable<Id, N>,
+		serializer: S,
+	) -> Result<S::Ok, S::Error>
+	where
+		S: Serializer,
+	{
+		serializer.collect_seq(ids.iter().map(|&id| SerialId(id)))
+	}
+
+	pub fn deserialize<'de, D, const N: usize>(
+		deserializer: D,
+	) -> Result<Resizable<Id, N>, D::Error>
+	where
+		D: Deserializer<'de>,
+	{
+		let ids = Vec::<SerialId>::deserialize(deserializer)?;
+
+		Ok(ids.into_iter().map(|id| id.0).collect())
+	}
+}
+
+pub mod resizable {
+	use list::resizable::Resizable;
+	use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+	pub fn serialize<S, T, const N: usize>(
+		list: &Resizable<T, N>,
+		serializer: S,
+	) -> Result<S::Ok, S::Error>
+	where
+		S: Serializer,
+		T: Serialize,
+	{
+		serializer.collect_seq(list.iter())
+	}
+
+	pub fn deserialize<'de, D, T, const N: usize>(
+		deserializer: D,
+	) -> Result<Resizable<T, N>, D::Error>
+	where
+		D: Deserializer<'de>,
+		T: Deserialize<'de>,
+	{
+		let list = Vec::<T>::deserialize(deserializer)?;
+
+		Ok(list.into_iter().collect())
+	}
+}
+
+/// Serializes the link as its node index and port, which does not depend on the graph
+/// it is in. Outside of a [`DataFlowGraph`], any node other than dangling is written.
+impl Serialize for Link {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.serialize_newtype_struct(LINK, &(SerialId(self.node), self.port))
+	}
+}
+
+/// Deserializes the link from its node index and port. Outside of a [`DataFlowGraph`],
+/// the node is given the [`Id`] that a fresh arena gives its index, which takes time
+/// linear in the index.
+impl<'de> Deserialize<'de> for Link {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		let RawLink(index, port) = RawLink::deserialize(deserializer)?;
+
+		Ok(Self {
+			node: find_fresh(index),
+			port,
+		})
+	}
+}
+
+struct NodeList<'a, T>(&'a DataFlowGraph<T>, &'a [Id]);
+
+impl<'a, T: Serialize> Serialize for NodeList<'a, T> {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		let nodes = self.1.iter().map(|&id| self.0.get(id));
+
+		serializer.collect_seq(nodes)
+	}
+}
+
+/// Serializes the nodes as a list with a slot for every index of the arena, so that
+/// every [`Id`] is written as its index alone. Links to nodes not in the graph are
+/// written as dangling. User lists are not serialized.
+/// The root [`Omega`] node, if any, is written by its index.
+///
+/// [`Omega`]: super::node::Omega
+impl<T: Serialize> Serialize for DataFlowGraph<T> {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		let len = self.indices_needed();
+
+		if u32::try_from(len).map_or(true, |len| len == DANGLING) {
+			return Err(S::Error::custom("too many nodes to serialize"));
+		}
+
+		let mut live = vec![Id::dangling(); len];
+
+		for (id, _) in self.iter() {
+			live[id] = id;
+		}
+
+		let _scope = Scope::enter(&LIVE, live.clone());
+		let mut state = serializer.serialize_struct("DataFlowGraph", 2)?;
+
+		state.serialize_field("nodes", &NodeList(self, &live))?;
+		state.serialize_field("omega", &self.root().map(SerialId))?;
+		state.end()
+	}
+}
+
+// The ids and links read from one node, in order. Links are read as dangling,
+// with their place in the list as the port, and ids are read as dangling.
+#[derive(Default)]
+struct Record {
+	ids: Vec<u32>,
+	links: Vec<(u32, u16)>,
+}
+
+// A reader that records the ids and links of a node instead of making them.
+struct Reader<'a, I> {
+	inner: I,
+	record: &'a mut Record,
+}
+
+impl<'a, I> Reader<'a, I> {
+	fn new(inner: I, record: &'a mut Record) -> Self {
+		Self { inner, record }
+	}
+}
+
+macro_rules! forward_deserialize {
+	($($method:ident)*) => {
+		$(
+			fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, D::Error> {
+				self.inner.$method(Reader::new(visitor, self.record))
+			}
+		)*
+	};
+}
+
+impl<'a, 'de, D: Deserializer<'de>> Deserializer<'de> for Reader<'a, D> {
+	type Error = D::Error;
+
+	forward_deserialize! {
+		deserialize_any deserialize_bool deserialize_char deserialize_str deserialize_string
+		deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64 deserialize_i128
+		deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64 deserialize_u128
+		deserialize_f32 deserialize_f64 deserialize_bytes deserialize_byte_buf
+		deserialize_option deserialize_unit deserialize_seq deserialize_map
+		deserialize_identifier deserialize_ignored_any
+	}
+
+	fn deserialize_unit_struct<V: Visitor<'de>>(
+		self,
+		name: &'static str,
+		visitor: V,
+	) -> Result<V::Value, D::Error> {
+		self.inner
+			.deserialize_unit_struct(name, Reader::new(visitor, self.record))
+	}
+
+	fn deserialize_newtype_struct<V: Visitor<'de>>(
+		self,
+		name: &'static str,
+		visitor: V,
+	) -> Result<V::Value, D::Error> {
+		match name {
+			ID => {
+				let RawId(index) = RawId::deserialize(self.inner)?;
+
+				self.record.ids.push(index);
+
+				visitor.visit_newtype_struct(DANGLING.into_deserializer())
+			}
+			LINK => {
+				let RawLink(index, port) = RawLink::deserialize(self.inner)?;
+				let place = u16::try_from(self.record.links.len())
+					.map_err(|_| D::Error::custom("too many links in one node"))?;
+
+				self.record.links.push((index, port));
+
+				let placeholder = [DANGLING, place.into()].into_iter();
+
+				visitor.visit_newtype_struct(SeqDeserializer::new(placeholder))
+			}
+			_ => self
+				.inner
+				.deserialize_newtype_struct(name, Reader::new(visitor, self.record)),
+		}
+	}
+
+	fn deserialize_tuple<V: Visitor<'de>>(
+		self,
+		len: usize,
+		visitor: V,
+	) -> Result<V::Value, D::Error> {
+		self.inner
+			.deserialize_tuple(len, Reader::new(visitor, self.record))
+	}
+
+	fn deserialize_tuple_struct<V: Visitor<'de>>(
+		self,
+		name: &'static str,
+		len: usize,
+		visitor: V,
+	) -> Result<V::Value, D::Error> {
+		self.inner
+			.deserialize_tuple_struct(name, len, Reader::new(visitor, self.record))
+	}
+
+	fn deserialize_struct<V: Visitor<'de>>(
+		self,
+		name: &'static str,
+		fields: &'static [&'static str],
+		visitor: V,
+	) -> Result<V::Value, D::Error> {
+		self.inner
+			.deserialize_struct(name, fields, Reader::new(visitor, self.record))
+	}
+
+	fn deserialize_enum<V: Visitor<'de>>(
+		self,
+		name: &'static str,
+		variants: &'static [&'static str],
+		visitor: V,
+	) -> Result<V::Value, D::Error> {
+		self.inner
+			.deserialize_enum(name, variants, Reader::new(visitor, self.record))
+	}
+
+	fn is_human_readable(&self) -> bool {
+		self.inner.is_human_readable()
+	}
+}
+
+macro_rules! forward_visit {
+	($($method:ident($type:ty))*) => {
+		$(
+			fn $method<E: serde::de::Error>(self, value: $type) -> Result<Self::Value, E> {
+				self.inner.$method(value)
+			}
+		)*
+	};
+}
+
+impl<'a, 'de, V: Visitor<'de>> Visitor<'de> for Reader<'a, V> {
+	type Value = V::Value;
+
+	fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+		self.inner.expecting(formatter)
+	}
+
+	forward_visit! {
+		visit_bool(bool) visit_char(char)
+		visit_i8(i8) visit_i16(i16) visit_i32(i32) visit_i64(i64) visit_i128(i128)
+		visit_u8(u8) visit_u16(u16) visit_u32(u32) visit_u64(u64) visit_u128(u128)
+		visit_f32(f32) visit_f64(f64) visit_str(&str) visit_borrowed_str(&'de str)
+		visit_string(String) visit_bytes(&[u8]) visit_borrowed_bytes(&'de [u8])
+		visit_byte_buf(Vec<u8>)
+	}
+
+	fn visit_none<E: serde::de::Error>(self) -> Result<Self::Value, E> {
+		self.inner.visit_none()
+	}
+
+	fn visit_unit<E: serde::de::Error>(self) -> Result<Self::Value, E> {
+		self.inner.visit_unit()
+	}
+
+	fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+		self.inner
+			.visit_some(Reader::new(deserializer, self.record))
+	}
+
+	fn visit_newtype_struct<D: Deserializer<'de>>(
+		self,
+		deserializer: D,
+	) -> Result<Self::Value, D::Error> {
+		self.inner
+			.visit_newtype_struct(Reader::new(deserializer, self.record))
+	}
+
+	fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+		self.inner.visit_seq(Reader::new(seq, self.record))
+	}
+
+	fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+		self.inner.visit_map(Reader::new(map, self.record))
+	}
+
+	fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<Self::Value, A::Error> {
+		self.inner.visit_enum(Reader::new(data, self.record))
+	}
+}
+
+impl<'a, 'de, S: DeserializeSeed<'de>> DeserializeSeed<'de> for Reader<'a, S> {
+	type Value = S::Value;
+
+	fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+		self.inner
+			.deserialize(Reader::new(deserializer, self.record))
+	}
+}
+
+impl<'a, 'de, A: SeqAccess<'de>> SeqAccess<'de> for Reader<'a, A> {
+	type Error = A::Error;
+
+	fn next_element_seed<S: DeserializeSeed<'de>>(
+		&mut self,
+		seed: S,
+	) -> Result<Option<S::Value>, A::Error> {
+		self.inner.next_element_seed(Reader::new(seed, self.record))
+	}
+
+	fn size_hint(&self) -> Option<usize> {
+		self.inner.size_hint()
+	}
+}
+
+impl<'a, 'de, A: MapAccess<'de>> MapAccess<'de> for Reader<'a, A> {
+	type Error = A::Error;
+
+	fn next_key_seed<S: DeserializeSeed<'de>>(
+		&mut self,
+		seed: S,
+	) -> Result<Option<S::Value>, A::Error> {
+		self.inner.next_key_seed(Reader::new(seed, self.record))
+	}
+
+	fn next_value_seed<S: DeserializeSeed<'de>>(&mut self, seed: S) -> Result<S::Value, A::Error> {
+		self.inner.next_value_seed(Reader::new(seed, self.record))
+	}
+
+	fn size_hint(&self) -> Option<usize> {
+		self.inner.size_hint()
+	}
+}
+
+impl<'a, 'de, A: EnumAccess<'de>> EnumAccess<'de> for Reader<'a, A> {
+	type Error = A::Error;
+	type Variant = Reader<'a, A::Variant>;
+
+	fn variant_seed<S: DeserializeSeed<'de>>(
+		self,
+		seed: S,
+	) -> Result<(S::Value, Self::Variant), A::Error> {
+		let record = self.record;
+		let (value, variant) = self.inner.variant_seed(Reader::new(seed, record))?;
+
+		Ok((value, Reader::new(variant, record)))
+	}
+}
+
+impl<'a, 'de, A: VariantAccess<'de>> VariantAccess<'de> for Reader<'a, A> {
+	type Error = A::Error;
+
+	fn unit_variant(self) -> Result<(), A::Error> {
+		self.inner.unit_variant()
+	}
+
+	fn newtype_variant_seed<S: DeserializeSeed<'de>>(self, seed: S) -> Result<S::Value, A::Error> {
+		self.inner
+			.newtype_variant_seed(Reader::new(seed, self.record))
+	}
+
+	fn tuple_variant<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, A::Error> {
+		self.inner
+			.tuple_variant(len, Reader::new(visitor, self.record))
+	}
+
+	fn struct_variant<V: Visitor<'de>>(
+		self,
+		fields: &'static [&'static str],
+		visitor: V,
+	) -> Result<V::Value, A::Error> {
+		self.inner
+			.struct_variant(fields, Reader::new(visitor, self.record))
+	}
+}
+
+struct Entry<T>(PhantomData<T>);
+
+impl<'de, T: Deserialize<'de>> DeserializeSeed<'de> for Entry<T> {
+	type Value = (Option<Node<T>>, Record);
+
+	fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+		let mut record = Record::default();
+		let node = Deserialize::deserialize(Reader::new(deserializer, &mut record))?;
+
+		Ok((node, record))
+	}
+}
+
+struct Entries<T>(Vec<(Option<Node<T>>, Record)>);
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Entries<T> {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		struct EntriesVisitor<T>(PhantomData<T>);
+
+		impl<'de, T: Deserialize<'de>> Visitor<'de> for EntriesVisitor<T> {
+			type Value = Entries<T>;
+
+			fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+				formatter.write_str("a list of nodes")
+			}
+
+			fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+				let mut entries = Vec::new();
+
+				while let Some(entry) = seq.next_element_seed(Entry(PhantomData))? {
+					entries.push(entry);
+				}
+
+				Ok(Entries(entries))
+			}
+		}
+
+		deserializer.deserialize_seq(EntriesVisitor(PhantomData))
+	}
+}
+
+#[derive(Deserialize)]
+#[serde(field_identifier, rename_all = "lowercase")]
+enum Field {
+	Nodes,
+	Omega,
+}
+
+struct GraphVisitor<T>(PhantomData<T>);
+
+impl<T: ParametersMut> GraphVisitor<T> {
+	fn resolve<E: serde::de::Error>(slots: &[Option<Id>], index: u32) -> Result<Id, E> {
+		if index == DANGLING {
+			return Ok(Id::dangling());
+		}
+
+		usize::try_from(index)
+			.ok()
+			.and_then(|index| slots.get(index).copied().flatten())
+			.ok_or_else(|| E::custom(format_args!("node index {index} is out of range")))
+	}
+
+	fn resolve_node<E: serde::de::Error>(
+		slots: &[Option<Id>],
+		node: &mut Node<T>,
+		record: &Record,
+	) -> Result<(), E> {
+		let ids = match node {
+			Node::Argument(argument) => std::slice::from_mut(&mut argument.parent),
+			_ => node.as_mut_arguments().unwrap_or_default(),
+		};
+
+		if ids.len() != record.ids.len() {
+			return Err(E::custom("node ids do not match the node"));
+		}
+
+		for (id, &index) in ids.iter_mut().zip(&record.ids) {
+			*id = Self::resolve(slots, index)?;
+		}
+
+		// Links were read as dangling, with their place in the record as the port.
+		let mut count = 0;
+		let mut resolve_link = |link: &mut Link| {
+			let &(index, port) = record
+				.links
+				.get(usize::from(link.port))
+				.filter(|_| link.node == Id::dangling())
+				.ok_or_else(|| E::custom("node links do not match the node"))?;
+
+			count += 1;
+
+			*link = Link {
+				node: Self::resolve(slots, index)?,
+				port,
+			};
+
+			Ok(())
+		};
+
+		node.parameters_mut().try_for_each(&mut resolve_link)?;
+
+		if let Some(results) = node.as_mut_results() {
+			results
+				.iter_mut()
+				.flatten()
+				.try_for_each(&mut resolve_link)?;
+		}
+
+		if count != record.links.len() {
+			return Err(E::custom("node links do not match the node"));
+		}
+
+		Ok(())
+	}
+
+	fn fill<E: serde::de::Error>(
+		entries: Vec<(Option<Node<T>>, Record)>,
+		omega: Option<RawId>,
+	) -> Result<DataFlowGraph<T>, E> {
+		let mut graph = DataFlowGraph::with_capacity(entries.len());
+		let mut slots = Vec::with_capacity(entries.len());
+		let mut holes = Vec::new();
+		let mut records = Vec::with_capacity(entries.len());
+
+		// A fresh arena gives every index in turn, so holes are filled in and
+		// removed only once every index is taken.
+		for (node, record) in entries {
+			if let Some(node) = node {
+				slots.push(Some(graph.nodes_mut().insert(node)));
+			} else {
+				holes.push(graph.add_argument().node);
+				slots.push(None);
+			}
+
+			records.push(record);
+		}
+
+		// Indices are only resolved now that the real number of nodes is known.
+		for (&id, record) in slots.iter().zip(&records) {
+			if let Some(id) = id {
+				Self::resolve_node(&slots, &mut graph.nodes_mut()[id], record)?;
+			}
+		}
+
+		for id in holes {
+			graph.nodes_mut().remove(id);
+		}
+
+		let omega = omega
+			.map(|RawId(index)| Self::resolve(&slots, index))
+			.transpose()?;
+
+		graph.set_root(omega);
+
+		Ok(graph)
+	}
+}
+
+impl<'de, T: Deserialize<'de> + ParametersMut> Visitor<'de> for GraphVisitor<T> {
+	type Value = DataFlowGraph<T>;
+
+	fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+		formatter.write_str("struct DataFlowGraph")
+	}
+
+	fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+		let Entries(entries) = seq
+			.next_element()?
+			.ok_or_else(|| A::Error::invalid_length(0, &self))?;
+		let omega = seq.next_element()?.flatten();
+
+		Self::fill(entries, omega)
+	}
+
+	fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+		let mut entries = None;
+		let mut omega = None;
+
+		while let Some(field) = map.next_key()? {
+			match field {
+				Field::Nodes if entries.is_some() => {
+					return Err(A::Error::duplicate_field("nodes"));
+				}
+				Field::Nodes => entries = Some(map.next_value::<Entries<T>>()?.0),
+				Field::Omega if omega.is_some() => return Err(A::Error::duplicate_field("omega")),
+				Field::Omega => omega = Some(map.next_value()?),
+			}
+		}
+
+		let entries = entries.ok_or_else(|| A::Error::missing_field("nodes"))?;
+
+		Self::fill(entries, omega.flatten())
+	}
+}
+
+/// Deserializes the nodes from a list, giving every node the index it was written with.
+/// Indices are resolved once the whole list is read, so they are checked against the
+/// real number of nodes. Links of simple nodes are found through [`ParametersMut`].
+/// User lists are not tracked on the resulting graph.
+impl<'de, T: Deserialize<'de> + ParametersMut> Deserialize<'de> for DataFlowGraph<T> {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		deserializer.deserialize_struct(
+			"DataFlowGraph",
+			&["nodes", "omega"],
+			GraphVisitor(PhantomData),
+		)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use arena::referent::Referent;
+	use serde::{Deserialize, Serialize};
+
+	use crate::collection::{
+		data_flow_graph::DataFlowGraph,
+		link::{Id, Link},
+		node::{Parameters, ParametersMut},
+	};
+
+	#[derive(Serialize, Deserialize, PartialEq, Debug)]
+	struct Simple(u8, Vec<Link>);
+
+	impl Parameters for Simple {
+		type Iter<'a> = std::slice::Iter<'a, Link>;
+
+		fn parameters(&self) -> Self::Iter<'_> {
+			self.1.iter()
+		}
+	}
+
+	impl ParametersMut for Simple {
+		type IterMut<'a> = std::slice::IterMut<'a, Link>;
+
+		fn parameters_mut(&mut self) -> Self::IterMut<'_> {
+			self.1.iter_mut()
+		}
+	}
+
+	fn round_trip(link: Link) -> Link {
+		serde_json::from_str(&serde_json::to_string(&link).unwrap()).unwrap()
+	}
+
+	#[test]
+	fn test_round_trip_keeps_indices() {
+		let mut nodes = DataFlowGraph::new();
+
+		let argument = nodes.add_argument();
+		let node_1 = nodes.add_simple(Simple(1, Vec::new()));
+		let node_2 = nodes.add_simple(Simple(2, vec![argument]));
+
+		let lambda = nodes.add_lambda(Vec::new(), 1, argument.node, vec![node_2, argument]);
+		let region = nodes.add_argument();
+		let omega = nodes.add_omega(Vec::new(), region.node, Vec::new(), vec![lambda]);
+
+		nodes.nodes_mut().remove(node_1.node);
+
+		let text = serde_json::to_string(&nodes).unwrap();
+		let restored: DataFlowGraph<Simple> = serde_json::from_str(&text).unwrap();
+
+		assert_eq!(restored.iter().count(), nodes.iter().count());
+		assert!(restored
+			.iter()
+			.all(|(id, _)| id.index() != node_1.node.index()));
+		assert_eq!(restored.root().map(Referent::index), Some(omega.index()));
+
+		for (id, node) in nodes.iter() {
+			let restored_link = round_trip(Link { node: id, port: 0 });
+
+			assert_eq!(restored_link.node.index(), id.index());
+			assert_eq!(restored[restored_link.node].as_simple(), node.as_simple());
+		}
+
+		let results = &restored[lambda.node].as_results().unwrap()[0];
+		let parameters = &restored[results[0].node].as_simple().unwrap().1;
+
+		assert_eq!(results.len(), 2);
+		assert_eq!(restored[results[0].node].as_simple().unwrap().0, 2);
+		assert_eq!(results[1].node.index(), argument.node.index());
+		assert_eq!(parameters, &[results[1]]);
+		assert_eq!(serde_json::to_string(&restored).unwrap(), text);
+	}
+
+	#[test]
+	fn test_reads_link_on_fresh_thread() {
+		let mut nodes = DataFlowGraph::<Simple>::new();
+
+		let _node_1 = nodes.add_simple(Simple(1, Vec::new()));
+		let node_2 = nodes.add_simple(Simple(2, Vec::new()));
+
+		let restored = std::thread::spawn(move || round_trip(node_2))
+			.join()
+			.unwrap();
+
+		assert_eq!(restored.node.index(), node_2.node.index());
+		assert_eq!(restored.port, node_2.port);
+		assert_eq!(round_trip(Link::from(Id::dangling())).node, Id::dangling());
+	}
+
+	#[test]
+	fn test_rejects_out_of_range() {
+		let result = serde_json::from_str::<DataFlowGraph<Simple>>(
+			r#"{"nodes":[{"Lambda":{"parameters":[],"inputs":0,"argument":7,"results":[]}}],"omega":null}"#,
+		);
+
+		assert!(result.is_err());
+
+		let result = serde_json::from_str::<DataFlowGraph<Simple>>(
+			r#"{"nodes":[{"Simple":[1,[[4000000000,0]]]}],"omega":null}"#,
+		);
+
+		assert!(result.is_err());
+	}
+}
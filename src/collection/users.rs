@@ -0,0 +1,261 @@
+use std::collections::HashMap;
+
+use super::{
+	data_flow_graph::DataFlowGraph,
+	link::{Id, Link},
+	node::{Node, Parameters},
+};
+
+/// A place where a [`Link`] is read from.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Use {
+	/// The `index`th parameter of the `node`.
+	Parameter { node: Id, index: usize },
+
+	/// The `index`th result of the region `region` of the `node`.
+	Result {
+		node: Id,
+		region: usize,
+		index: usize,
+	},
+}
+
+impl Use {
+	/// Returns the node that reads the [`Link`].
+	#[inline]
+	#[must_use]
+	pub const fn node(self) -> Id {
+		match self {
+			Self::Parameter { node, .. } | Self::Result { node, .. } => node,
+		}
+	}
+}
+
+/// A list of users for every output port of a graph.
+#[derive(Default)]
+pub struct Users {
+	lists: HashMap<Link, Vec<Use>>,
+}
+
+impl Users {
+	/// Creates a new, empty [`Users`] instance.
+	#[inline]
+	#[must_use]
+	pub fn new() -> Self {
+		Self {
+			lists: HashMap::new(),
+		}
+	}
+
+	/// Returns the users of the [`Link`], in no particular order.
+	#[inline]
+	#[must_use]
+	pub fn users_of(&self, link: Link) -> &[Use] {
+		self.lists.get(&link).map_or(&[], Vec::as_slice)
+	}
+
+	/// Clears all user lists.
+	pub fn clear(&mut self) {
+		self.lists.clear();
+	}
+
+	/// Rebuilds all user lists from the nodes of the graph.
+	pub fn build<T: Parameters>(&mut self, nodes: &DataFlowGraph<T>) {
+		self.clear();
+
+		for (id, node) in nodes.iter() {
+			self.insert_node(id, node);
+		}
+	}
+
+	pub(crate) fn insert(&mut self, link: Link, user: Use) {
+		// Dangling links, such as unread results, have no node to be used by.
+		if link == Link::dangling() {
+			return;
+		}
+
+		self.lists.entry(link).or_default().push(user);
+	}
+
+	pub(crate) fn remove(&mut self, link: Link, user: Use) {
+		let Some(list) = self.lists.get_mut(&link) else {
+			return;
+		};
+
+		if let Some(index) = list.iter().position(|&other| other == user) {
+			list.swap_remove(index);
+		}
+
+		if list.is_empty() {
+			self.lists.remove(&link);
+		}
+	}
+
+	pub(crate) fn insert_links<'a, I>(&mut self, id: Id, parameters: I, results: &[Vec<Link>])
+	where
+		I: IntoIterator<Item = &'a Link>,
+	{
+		for (index, &link) in parameters.into_iter().enumerate() {
+			self.insert(link, Use::Parameter { node: id, index });
+		}
+
+		for (region, list) in results.iter().enumerate() {
+			for (index, &link) in list.iter().enumerate() {
+				self.insert(
+					link,
+					Use::Result {
+						node: id,
+						region,
+						index,
+					},
+				);
+			}
+		}
+	}
+
+	pub(crate) fn remove_links<'a, I>(&mut self, id: Id, parameters: I, results: &[Vec<Link>])
+	where
+		I: IntoIterator<Item = &'a Link>,
+	{
+		for (index, &link) in parameters.into_iter().enumerate() {
+			self.remove(link, Use::Parameter { node: id, index });
+		}
+
+		for (region, list) in results.iter().enumerate() {
+			for (index, &link) in list.iter().enumerate() {
+				self.remove(
+					link,
+					Use::Result {
+						node: id,
+						region,
+						index,
+					},
+				);
+			}
+		}
+	}
+
+	pub(crate) fn insert_node<T: Parameters>(&mut self, id: Id, node: &Node<T>) {
+		self.insert_links(id, node.parameters(), node.as_results().unwrap_or_default());
+	}
+
+	pub(crate) fn remove_node<T: Parameters>(&mut self, id: Id, node: &Node<T>) {
+		self.remove_links(id, node.parameters(), node.as_results().unwrap_or_default());
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{collection::data_flow_graph::DataFlowGraph, fixture::Simple};
+
+	use super::Use;
+
+	fn sorted(uses: &[Use]) -> Vec<Use> {
+		let mut uses = uses.to_vec();
+
+		uses.sort_unstable();
+		uses
+	}
+
+	#[test]
+	fn test_tracks_added_nodes() {
+		let mut nodes = DataFlowGraph::<Simple>::new();
+
+		let node_0 = nodes.add_simple(Simple(Vec::new()));
+
+		nodes.track_users();
+
+		let node_1 = nodes.add_simple(Simple(vec![node_0, node_0]));
+		let argument = nodes.add_argument();
+		let node_2 = nodes.add_theta(vec![node_1], argument.node, vec![node_1, node_0]);
+
+		assert_eq!(
+			sorted(&nodes.users_of(node_0)),
+			[
+				Use::Parameter {
+					node: node_1.node,
+					index: 0
+				},
+				Use::Parameter {
+					node: node_1.node,
+					index: 1
+				},
+				Use::Result {
+					node: node_2.node,
+					region: 0,
+					index: 1
+				},
+			]
+		);
+		assert_eq!(nodes.users_of(node_1).len(), 2);
+		assert!(nodes.users_of(node_2).is_empty());
+	}
+
+	#[test]
+	fn test_tracks_modified_nodes() {
+		let mut nodes = DataFlowGraph::<Simple>::new();
+
+		nodes.track_users();
+
+		let node_0 = nodes.add_simple(Simple(Vec::new()));
+		let node_1 = nodes.add_simple(Simple(Vec::new()));
+		let node_2 = nodes.add_simple(Simple(vec![node_0]));
+
+		nodes.modify(node_2.node, |node| {
+			node.as_mut_simple().unwrap().0 = vec![node_1];
+		});
+
+		assert!(nodes.users_of(node_0).is_empty());
+		assert_eq!(
+			*nodes.users_of(node_1),
+			[Use::Parameter {
+				node: node_2.node,
+				index: 0
+			}]
+		);
+
+		let _ = nodes.remove_node(node_2.node);
+
+		assert!(nodes.users_of(node_1).is_empty());
+		assert!(nodes.users().is_some_and(|users| users.lists.is_empty()));
+	}
+
+	#[test]
+	fn test_tracks_replaced_uses() {
+		let mut nodes = DataFlowGraph::<Simple>::new();
+
+		nodes.track_users();
+
+		let node_0 = nodes.add_simple(Simple(Vec::new()));
+		let node_1 = nodes.add_simple(Simple(Vec::new()));
+		let node_2 = nodes.add_simple(Simple(vec![node_0, node_1]));
+		let node_3 = nodes.add_simple(Simple(vec![node_0]));
+
+		assert_eq!(nodes.replace_uses(node_0, node_1), 2);
+		assert!(nodes.users_of(node_0).is_empty());
+		assert_eq!(
+			sorted(&nodes.users_of(node_1)),
+			[
+				Use::Parameter {
+					node: node_2.node,
+					index: 0
+				},
+				Use::Parameter {
+					node: node_2.node,
+					index: 1
+				},
+				Use::Parameter {
+					node: node_3.node,
+					index: 0
+				},
+			]
+		);
+
+		// Rebuilding from scratch gives the same lists.
+		let before = sorted(&nodes.users_of(node_1));
+
+		nodes.track_users();
+
+		assert_eq!(sorted(&nodes.users_of(node_1)), before);
+	}
+}
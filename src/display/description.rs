@@ -40,10 +40,13 @@ impl<T: Description> Description for Node<T> {
 	fn write_content(&self, writer: &mut dyn Write) -> Result<()> {
 		let name = match self {
 			Self::Simple(node) => return node.write_content(writer),
+			Self::Argument(_) => "Argument",
 			Self::Gamma(_) => "Gamma",
 			Self::Theta(_) => "Theta",
 			Self::Phi(_) => "Phi",
 			Self::Lambda(_) => "Lambda",
+			Self::Delta(_) => "Delta",
+			Self::Omega(_) => "Omega",
 		};
 
 		write!(writer, "<TR><TD>{name}</TD></TR>")
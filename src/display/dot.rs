@@ -6,7 +6,7 @@ use crate::{
 	collection::{
 		data_flow_graph::DataFlowGraph,
 		link::{Id, Link},
-		node::Parameters,
+		node::{Arity, Parameters},
 	},
 	visit::depth_first_searcher::{DepthFirstSearcher, Event},
 };
@@ -130,12 +130,13 @@ impl Dot {
 		}
 	}
 
-	fn find_ports<T: Parameters>(&mut self, nodes: &DataFlowGraph<T>) {
+	fn find_ports<T: Parameters + Arity>(&mut self, nodes: &DataFlowGraph<T>) {
 		self.ports.clear();
 		self.ports.resize(nodes.indices_needed(), Ports::new(0, 0));
 
 		for &id in &self.nodes {
 			self.ports[id].set_input(nodes[id].parameters().count());
+			self.ports[id].set_outputs(nodes.arity(id).unwrap_or_default());
 
 			for &link in nodes[id].parameters() {
 				self.ports[link.node].set_output(link.port);
@@ -235,6 +236,9 @@ impl Dot {
 	}
 
 	/// Writes the data flow graph to the writer in the DOT format.
+	/// The nodes reachable from the `results` and the root [`Omega`] node, if any, are laid out.
+	///
+	/// [`Omega`]: crate::collection::node::Omega
 	///
 	/// # Errors
 	///
@@ -246,7 +250,7 @@ impl Dot {
 		results: I,
 	) -> Result<()>
 	where
-		T: Parameters + Description,
+		T: Parameters + Arity + Description,
 		I: IntoIterator<Item = Id>,
 	{
 		writeln!(write, "digraph {{")?;
@@ -255,7 +259,7 @@ impl Dot {
 			"\tnode [shape = plain, style = filled, ordering = out, color = \"#FFFFFF\", fontcolor = \"#FFFFFF\"];"
 		)?;
 
-		self.find_topological(nodes, results);
+		self.find_topological(nodes, results.into_iter().chain(nodes.root()));
 		self.find_ports(nodes);
 		self.write_node_bodies(write, nodes)?;
 		self.write_node_links(write, nodes)?;
@@ -24,6 +24,10 @@ impl Ports {
 	pub fn set_output(&mut self, outputs: u16) {
 		self.output = self.output.max(outputs + 1);
 	}
+
+	pub fn set_outputs(&mut self, outputs: usize) {
+		self.output = self.output.max(outputs.try_into().unwrap_or(u16::MAX));
+	}
 }
 
 fn write_port_list<F>(write: &mut dyn Write, len: usize, side: &str, function: F) -> Result<()>
@@ -0,0 +1,70 @@
+//! Nodes shared by the tests of the graph and its transforms.
+
+use crate::{
+	collection::{
+		link::Link,
+		node::{Arity, Parameters, ParametersMut},
+	},
+	transform::value_numberer::Value,
+};
+
+/// A simple node with one output.
+#[derive(Clone)]
+pub struct Simple(pub Vec<Link>);
+
+impl Parameters for Simple {
+	type Iter<'a> = std::slice::Iter<'a, Link>;
+
+	fn parameters(&self) -> Self::Iter<'_> {
+		self.0.iter()
+	}
+}
+
+impl ParametersMut for Simple {
+	type IterMut<'a> = std::slice::IterMut<'a, Link>;
+
+	fn parameters_mut(&mut self) -> Self::IterMut<'_> {
+		self.0.iter_mut()
+	}
+}
+
+impl Arity for Simple {
+	fn arity(&self) -> usize {
+		1
+	}
+}
+
+/// A simple node with one output, keyed by its name unless it is `"print"`,
+/// which stands for a node with side effects.
+#[derive(Clone)]
+pub struct Named(pub &'static str, pub Vec<Link>);
+
+impl Parameters for Named {
+	type Iter<'a> = std::slice::Iter<'a, Link>;
+
+	fn parameters(&self) -> Self::Iter<'_> {
+		self.1.iter()
+	}
+}
+
+impl ParametersMut for Named {
+	type IterMut<'a> = std::slice::IterMut<'a, Link>;
+
+	fn parameters_mut(&mut self) -> Self::IterMut<'_> {
+		self.1.iter_mut()
+	}
+}
+
+impl Arity for Named {
+	fn arity(&self) -> usize {
+		1
+	}
+}
+
+impl Value for Named {
+	type Key = &'static str;
+
+	fn key(&self) -> Option<Self::Key> {
+		(self.0 != "print").then_some(self.0)
+	}
+}
@@ -1,5 +1,13 @@
 pub mod collection;
+pub mod transform;
+pub mod verify;
 pub mod visit;
 
+#[cfg(test)]
+mod fixture;
+
 #[cfg(feature = "display")]
 pub mod display;
+
+#[cfg(feature = "text")]
+pub mod text;
@@ -0,0 +1,723 @@
+use std::collections::HashMap;
+
+use crate::collection::{
+	data_flow_graph::DataFlowGraph,
+	link::{Id, Link},
+	node::{Argument, Delta, Gamma, Lambda, Node, Omega, Phi, Theta},
+};
+
+use super::payload::Parse;
+
+/// The reason a text could not be parsed.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+	/// The line does not have the expected shape.
+	Expected(&'static str),
+
+	/// The payload of a simple node was rejected by [`Parse`].
+	Payload,
+
+	/// The name was defined more than once.
+	Redefined(String),
+
+	/// The name was used but never defined.
+	Undefined(String),
+
+	/// The text ended inside of a block.
+	Unclosed,
+}
+
+/// An error found while parsing, along with the line it was found on, starting at 1.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Error {
+	pub line: usize,
+	pub kind: ErrorKind,
+}
+
+impl Error {
+	const fn expected(line: usize, what: &'static str) -> Self {
+		Self {
+			line,
+			kind: ErrorKind::Expected(what),
+		}
+	}
+}
+
+impl std::error::Error for Error {}
+
+impl std::fmt::Display for Error {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "line {}: ", self.line)?;
+
+		match &self.kind {
+			ErrorKind::Expected(what) => write!(f, "expected {what}"),
+			ErrorKind::Payload => write!(f, "invalid payload"),
+			ErrorKind::Redefined(name) => write!(f, "`%{name}` is defined more than once"),
+			ErrorKind::Undefined(name) => write!(f, "`%{name}` is never defined"),
+			ErrorKind::Unclosed => write!(f, "unclosed block"),
+		}
+	}
+}
+
+fn parse_name(text: &str) -> Option<&str> {
+	let name = text.strip_prefix('%')?;
+	let is_valid = |c: char| c.is_alphanumeric() || c == '_';
+
+	(!name.is_empty() && name.chars().all(is_valid)).then_some(name)
+}
+
+fn parse_string(text: &str) -> Option<(String, &str)> {
+	let mut string = String::new();
+	let mut chars = text.strip_prefix('"')?.char_indices();
+
+	while let Some((index, c)) = chars.next() {
+		match c {
+			// Both quotes are one byte long.
+			'"' => return Some((string, &text[index + 2..])),
+			'\\' => match chars.next()?.1 {
+				'n' => string.push('\n'),
+				c => string.push(c),
+			},
+			c => string.push(c),
+		}
+	}
+
+	None
+}
+
+fn parse_names(text: &str) -> Option<(Vec<String>, &str)> {
+	let mut text = text.trim_start().strip_prefix('[')?.trim_start();
+	let mut names = Vec::new();
+
+	if let Some(text) = text.strip_prefix(']') {
+		return Some((names, text));
+	}
+
+	loop {
+		let (name, rest) = parse_string(text)?;
+		let rest = rest.trim_start();
+
+		names.push(name);
+
+		if let Some(rest) = rest.strip_prefix(']') {
+			return Some((names, rest));
+		}
+
+		text = rest.strip_prefix(',')?.trim_start();
+	}
+}
+
+fn find_definition(text: &str) -> Option<&str> {
+	let name = match text.strip_prefix("region ") {
+		Some(header) => header.strip_suffix(" {")?,
+		None => text.split_once(" = ")?.0,
+	};
+
+	parse_name(name.trim())
+}
+
+enum Kind {
+	Gamma,
+	Theta,
+	Phi,
+	Lambda {
+		inputs: usize,
+	},
+	Delta,
+	Omega {
+		imports: Vec<String>,
+		exports: Vec<String>,
+	},
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum State {
+	Regions,
+	Nodes,
+	Results,
+}
+
+struct Block {
+	id: Id,
+	kind: Kind,
+	state: State,
+
+	parameters: Vec<Link>,
+	arguments: Vec<Id>,
+	results: Vec<Vec<Link>>,
+}
+
+struct Name {
+	id: Id,
+	line: usize,
+	is_defined: bool,
+}
+
+/// A reader of the text format, as written by the [`Printer`].
+///
+/// Names may be used before they are defined, and every [`Id`] is given out
+/// in the order the names are defined.
+///
+/// [`Printer`]: super::printer::Printer
+pub struct Parser {
+	names: HashMap<String, Name>,
+	blocks: Vec<Block>,
+}
+
+impl Parser {
+	/// Creates a new, reusable [`Parser`] instance.
+	#[inline]
+	#[must_use]
+	pub fn new() -> Self {
+		Self {
+			names: HashMap::new(),
+			blocks: Vec::new(),
+		}
+	}
+
+	fn find_id<T>(&mut self, nodes: &mut DataFlowGraph<T>, name: &str, line: usize) -> Id {
+		if let Some(name) = self.names.get(name) {
+			return name.id;
+		}
+
+		let id = nodes.add_argument().node;
+
+		self.names.insert(
+			name.to_string(),
+			Name {
+				id,
+				line,
+				is_defined: false,
+			},
+		);
+
+		id
+	}
+
+	fn define<T>(
+		&mut self,
+		nodes: &mut DataFlowGraph<T>,
+		name: &str,
+		line: usize,
+	) -> Result<Id, Error> {
+		let id = self.find_id(nodes, name, line);
+		let name_data = self.names.get_mut(name).unwrap();
+
+		if name_data.is_defined {
+			return Err(Error {
+				line,
+				kind: ErrorKind::Redefined(name.to_string()),
+			});
+		}
+
+		name_data.is_defined = true;
+
+		Ok(id)
+	}
+
+	fn parse_link<T>(
+		&mut self,
+		nodes: &mut DataFlowGraph<T>,
+		text: &str,
+		line: usize,
+	) -> Result<Link, Error> {
+		let text = text.trim();
+
+		if text == "_" {
+			return Ok(Link::dangling());
+		}
+
+		let (name, port) = text.split_once(':').unwrap_or((text, "0"));
+		let name = parse_name(name).ok_or(Error::expected(line, "a link"))?;
+		let port = port.parse().map_err(|_| Error::expected(line, "a port"))?;
+
+		Ok(Link {
+			node: self.find_id(nodes, name, line),
+			port,
+		})
+	}
+
+	fn parse_links<T>(
+		&mut self,
+		nodes: &mut DataFlowGraph<T>,
+		text: &str,
+		line: usize,
+	) -> Result<Vec<Link>, Error> {
+		let list = text
+			.trim()
+			.strip_prefix('(')
+			.and_then(|text| text.strip_suffix(')'))
+			.ok_or(Error::expected(line, "a list of links"))?;
+
+		if list.trim().is_empty() {
+			return Ok(Vec::new());
+		}
+
+		list.split(',')
+			.map(|text| self.parse_link(nodes, text, line))
+			.collect()
+	}
+
+	fn open_block<T>(
+		&mut self,
+		nodes: &mut DataFlowGraph<T>,
+		id: Id,
+		text: &str,
+		line: usize,
+	) -> Result<(), Error> {
+		let (keyword, mut text) = text
+			.split_once(' ')
+			.ok_or(Error::expected(line, "a list of links"))?;
+
+		let kind = match keyword {
+			"gamma" => Kind::Gamma,
+			"theta" => Kind::Theta,
+			"phi" => Kind::Phi,
+			"delta" => Kind::Delta,
+			"omega" => {
+				let names = parse_names(text).and_then(|(imports, text)| {
+					let text = text.trim_start().strip_prefix("->")?;
+					let (exports, text) = parse_names(text)?;
+
+					text.trim().is_empty().then_some((imports, exports))
+				});
+				let (imports, exports) =
+					names.ok_or(Error::expected(line, "import and export names"))?;
+
+				Kind::Omega { imports, exports }
+			}
+			"lambda" => {
+				let (inputs, rest) = text
+					.split_once(' ')
+					.ok_or(Error::expected(line, "a list of links"))?;
+
+				text = rest;
+
+				Kind::Lambda {
+					inputs: inputs
+						.parse()
+						.map_err(|_| Error::expected(line, "an input count"))?,
+				}
+			}
+			_ => return Err(Error::expected(line, "a compound node")),
+		};
+
+		// Roots take no parameters, as their names were parsed instead.
+		let parameters = if let Kind::Omega { .. } = kind {
+			Vec::new()
+		} else {
+			self.parse_links(nodes, text, line)?
+		};
+
+		self.blocks.push(Block {
+			id,
+			kind,
+			state: State::Regions,
+
+			parameters,
+			arguments: Vec::new(),
+			results: Vec::new(),
+		});
+
+		Ok(())
+	}
+
+	fn close_block<T>(&mut self, nodes: &mut DataFlowGraph<T>, line: usize) -> Result<(), Error> {
+		let Some(block) = self.blocks.last_mut() else {
+			return Err(Error::expected(line, "a node definition"));
+		};
+
+		match block.state {
+			State::Regions => {}
+			State::Nodes => return Err(Error::expected(line, "`results`")),
+			State::Results => {
+				block.state = State::Regions;
+
+				return Ok(());
+			}
+		}
+
+		let mut block = self.blocks.pop().unwrap();
+		let parameters = block.parameters;
+
+		let node = if let Kind::Gamma = block.kind {
+			Node::Gamma(Gamma {
+				parameters,
+				arguments: block.arguments.into_iter().collect(),
+				results: block.results.into_iter().collect(),
+			})
+		} else {
+			let (&[argument], Some(results)) = (&block.arguments[..], block.results.pop()) else {
+				return Err(Error::expected(line, "a single region"));
+			};
+
+			match block.kind {
+				Kind::Gamma => unreachable!(),
+				Kind::Theta => Node::Theta(Theta {
+					parameters,
+					argument,
+					results,
+				}),
+				Kind::Phi => Node::Phi(Phi {
+					parameters,
+					argument,
+					results,
+				}),
+				Kind::Lambda { inputs } => Node::Lambda(Lambda {
+					parameters,
+					inputs,
+					argument,
+					results,
+				}),
+				Kind::Delta => Node::Delta(Delta {
+					parameters,
+					argument,
+					results,
+				}),
+				Kind::Omega { imports, exports } => {
+					// The first root read is the root of the graph.
+					if nodes.root().is_none() {
+						nodes.set_root(Some(block.id));
+					}
+
+					Node::Omega(Omega {
+						imports,
+						argument,
+						exports,
+						results,
+					})
+				}
+			}
+		};
+
+		nodes[block.id] = node;
+
+		Ok(())
+	}
+
+	fn open_region<T>(
+		&mut self,
+		nodes: &mut DataFlowGraph<T>,
+		text: &str,
+		line: usize,
+	) -> Result<(), Error> {
+		let name = text
+			.strip_suffix(" {")
+			.ok_or(Error::expected(line, "`{`"))?
+			.trim();
+
+		let argument = if name == "_" {
+			None
+		} else {
+			let name = parse_name(name).ok_or(Error::expected(line, "a region name"))?;
+
+			Some(self.define(nodes, name, line)?)
+		};
+
+		let Some(block) = self.blocks.last_mut() else {
+			return Err(Error::expected(line, "a node definition"));
+		};
+
+		let is_full = !matches!(block.kind, Kind::Gamma) && !block.arguments.is_empty();
+
+		if block.state != State::Regions || is_full {
+			return Err(Error::expected(line, "`}`"));
+		}
+
+		let argument = argument.map_or_else(Id::dangling, |argument| {
+			nodes[argument] = Node::Argument(Argument {
+				parent: block.id,
+				region: block.arguments.len(),
+			});
+
+			argument
+		});
+
+		block.arguments.push(argument);
+		block.state = State::Nodes;
+
+		Ok(())
+	}
+
+	fn parse_results<T>(
+		&mut self,
+		nodes: &mut DataFlowGraph<T>,
+		text: &str,
+		line: usize,
+	) -> Result<(), Error> {
+		if self.blocks.last().map(|block| block.state) != Some(State::Nodes) {
+			return Err(Error::expected(line, "a node definition"));
+		}
+
+		let results = self.parse_links(nodes, text, line)?;
+		let block = self.blocks.last_mut().unwrap();
+
+		block.results.push(results);
+		block.state = State::Results;
+
+		Ok(())
+	}
+
+	fn parse_definition<T: Parse>(
+		&mut self,
+		nodes: &mut DataFlowGraph<T>,
+		name: &str,
+		text: &str,
+		line: usize,
+	) -> Result<(), Error> {
+		match self.blocks.last().map(|block| block.state) {
+			None | Some(State::Nodes) => {}
+			Some(State::Regions) => return Err(Error::expected(line, "`region` or `}`")),
+			Some(State::Results) => return Err(Error::expected(line, "`}`")),
+		}
+
+		let name = parse_name(name.trim()).ok_or(Error::expected(line, "a node name"))?;
+		let id = self.define(nodes, name, line)?;
+		let text = text.trim();
+
+		if text == "argument" {
+			nodes[id] = Node::Argument(Argument {
+				parent: Id::dangling(),
+				region: 0,
+			});
+
+			return Ok(());
+		}
+
+		if let Some(text) = text.strip_suffix(" {") {
+			return self.open_block(nodes, id, text, line);
+		}
+
+		let start = text
+			.rfind('(')
+			.ok_or(Error::expected(line, "a list of links"))?;
+		let parameters = self.parse_links(nodes, &text[start..], line)?;
+		let simple = T::parse(text[..start].trim(), parameters).ok_or(Error {
+			line,
+			kind: ErrorKind::Payload,
+		})?;
+
+		nodes[id] = Node::Simple(simple);
+
+		Ok(())
+	}
+
+	fn parse_line<T: Parse>(
+		&mut self,
+		nodes: &mut DataFlowGraph<T>,
+		text: &str,
+		line: usize,
+	) -> Result<(), Error> {
+		if text == "}" {
+			self.close_block(nodes, line)
+		} else if let Some(text) = text.strip_prefix("region ") {
+			self.open_region(nodes, text, line)
+		} else if let Some(text) = text.strip_prefix("results ") {
+			self.parse_results(nodes, text, line)
+		} else if let Some((name, text)) = text.split_once(" = ") {
+			self.parse_definition(nodes, name, text, line)
+		} else {
+			Err(Error::expected(line, "a node definition"))
+		}
+	}
+
+	/// Reads a data flow graph from the text format.
+	///
+	/// # Errors
+	///
+	/// Returns an error if the text is not valid, or if [`Parse`] rejects a payload.
+	pub fn run<T: Parse>(&mut self, text: &str) -> Result<DataFlowGraph<T>, Error> {
+		let mut nodes = DataFlowGraph::new();
+
+		self.names.clear();
+		self.blocks.clear();
+
+		// Definitions are given their ids up front, so the order of the text is kept.
+		for (index, text) in text.lines().enumerate() {
+			if let Some(name) = find_definition(text.trim()) {
+				self.find_id(&mut nodes, name, index + 1);
+			}
+		}
+
+		let mut last = 0;
+
+		for (index, text) in text.lines().enumerate() {
+			let text = text.trim();
+
+			last = index + 1;
+
+			if !text.is_empty() {
+				self.parse_line(&mut nodes, text, last)?;
+			}
+		}
+
+		if !self.blocks.is_empty() {
+			return Err(Error {
+				line: last,
+				kind: ErrorKind::Unclosed,
+			});
+		}
+
+		let undefined = self
+			.names
+			.iter()
+			.filter(|(_, name)| !name.is_defined)
+			.min_by_key(|(_, name)| name.line);
+
+		if let Some((name, data)) = undefined {
+			return Err(Error {
+				line: data.line,
+				kind: ErrorKind::Undefined(name.clone()),
+			});
+		}
+
+		Ok(nodes)
+	}
+}
+
+impl Default for Parser {
+	#[inline]
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		collection::{data_flow_graph::DataFlowGraph, link::Link, node::Parameters},
+		text::{
+			payload::{Parse, Print},
+			printer::Printer,
+		},
+	};
+
+	use super::{ErrorKind, Parser};
+
+	struct Simple {
+		name: String,
+		parameters: Vec<Link>,
+	}
+
+	impl Simple {
+		fn new(name: &str, parameters: Vec<Link>) -> Self {
+			Self {
+				name: name.to_string(),
+				parameters,
+			}
+		}
+	}
+
+	impl Parameters for Simple {
+		type Iter<'a> = std::slice::Iter<'a, Link>;
+
+		fn parameters(&self) -> Self::Iter<'_> {
+			self.parameters.iter()
+		}
+	}
+
+	impl Print for Simple {
+		fn print(&self, writer: &mut dyn std::io::Write) -> std::io::Result<()> {
+			write!(writer, "{}", self.name)
+		}
+	}
+
+	impl Parse for Simple {
+		fn parse(payload: &str, parameters: Vec<Link>) -> Option<Self> {
+			let is_valid = !payload.is_empty() && payload.chars().all(char::is_alphanumeric);
+
+			is_valid.then(|| Self::new(payload, parameters))
+		}
+	}
+
+	fn print(nodes: &DataFlowGraph<Simple>) -> String {
+		let mut text = Vec::new();
+
+		Printer::new().write(&mut text, nodes).unwrap();
+
+		String::from_utf8(text).unwrap()
+	}
+
+	#[test]
+	fn test_round_trip() {
+		let mut nodes = DataFlowGraph::new();
+
+		let node_0 = nodes.add_simple(Simple::new("input", Vec::new()));
+		let argument_0 = nodes.add_argument();
+		let node_1 = nodes.add_simple(Simple::new("negate", vec![argument_0]));
+		let argument_1 = nodes.add_argument();
+		let argument_2 = nodes.add_argument();
+		let node_2 = nodes.add_gamma(
+			vec![argument_0, node_1],
+			[argument_1.node, argument_2.node].into_iter().collect(),
+			[vec![argument_1], vec![Link::dangling()]]
+				.into_iter()
+				.collect(),
+		);
+		let node_3 = nodes.add_theta(vec![node_0], argument_0.node, vec![node_2, node_1]);
+
+		let _ = nodes.add_simple(Simple::new("output", vec![node_3]));
+
+		let text = print(&nodes);
+
+		assert_eq!(
+			text,
+			"%0 = input ()\n\
+			%1 = theta (%0:0) {\n\
+			\tregion %2 {\n\
+			\t\t%3 = negate (%2:0)\n\
+			\t\t%4 = gamma (%2:0, %3:0) {\n\
+			\t\t\tregion %5 {\n\
+			\t\t\t\tresults (%5:0)\n\
+			\t\t\t}\n\
+			\t\t\tregion %6 {\n\
+			\t\t\t\tresults (_)\n\
+			\t\t\t}\n\
+			\t\t}\n\
+			\t\tresults (%4:0, %3:0)\n\
+			\t}\n\
+			}\n\
+			%7 = output (%1:0)\n"
+		);
+
+		let parsed = Parser::new().run::<Simple>(&text).unwrap();
+
+		assert_eq!(print(&parsed), text);
+	}
+
+	#[test]
+	fn test_round_trip_root() {
+		let text = "%0 = omega [\"print\", \"a \\\"b\\\"\"] -> [\"main\"] {\n\
+			\tregion %1 {\n\
+			\t\t%2 = call (%1:0, %1:1)\n\
+			\t\tresults (%2:0)\n\
+			\t}\n\
+			}\n";
+
+		let parsed = Parser::new().run::<Simple>(text).unwrap();
+		let root = parsed[parsed.root().unwrap()].as_omega().unwrap();
+
+		assert_eq!(root.imports, ["print", "a \"b\""]);
+		assert_eq!(print(&parsed), text);
+	}
+
+	#[test]
+	fn test_reports_errors() {
+		let run = |text| Parser::new().run::<Simple>(text).map(|_| ()).unwrap_err();
+
+		assert_eq!(
+			run("%a = add (%b:0)").kind,
+			ErrorKind::Undefined("b".into())
+		);
+		assert_eq!(run("%a = add ()\n%a = add ()").line, 2);
+		assert_eq!(run("%a = ! ()").kind, ErrorKind::Payload);
+		assert_eq!(
+			run("%a = theta () {\n\tregion %b {").kind,
+			ErrorKind::Unclosed
+		);
+		assert_eq!(
+			run("%a = theta () {\n\tregion %b {\n\t}\n}").kind,
+			ErrorKind::Expected("`results`")
+		);
+	}
+}
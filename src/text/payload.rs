@@ -0,0 +1,21 @@
+use std::io::{Result, Write};
+
+use crate::collection::link::Link;
+
+/// A trait for describing how a simple node should be written to the text format.
+pub trait Print {
+	/// Write the payload of the node, without its parameters.
+	/// It must fit on a single line.
+	///
+	/// # Errors
+	///
+	/// Returns an error if writing to the writer fails.
+	fn print(&self, writer: &mut dyn Write) -> Result<()>;
+}
+
+/// A trait for describing how a simple node should be read from the text format.
+pub trait Parse: Sized {
+	/// Creates the node from its payload, as written by [`Print`], and its parameters.
+	/// Returns `None` if the payload is not valid.
+	fn parse(payload: &str, parameters: Vec<Link>) -> Option<Self>;
+}
@@ -0,0 +1,341 @@
+use std::{
+	collections::HashMap,
+	io::{Result, Write},
+	ops::Range,
+};
+
+use crate::{
+	collection::{
+		data_flow_graph::DataFlowGraph,
+		link::{Id, Link},
+		node::{Node, Parameters},
+	},
+	visit::region_finder::RegionFinder,
+};
+
+use super::payload::Print;
+
+const UNNAMED: usize = usize::MAX;
+
+fn write_names(write: &mut dyn Write, names: &[String]) -> Result<()> {
+	write!(write, "[")?;
+
+	for (index, name) in names.iter().enumerate() {
+		if index != 0 {
+			write!(write, ", ")?;
+		}
+
+		write!(write, "\"")?;
+
+		for c in name.chars() {
+			match c {
+				'"' | '\\' => write!(write, "\\{c}")?,
+				'\n' => write!(write, "\\n")?,
+				c => write!(write, "{c}")?,
+			}
+		}
+
+		write!(write, "\"")?;
+	}
+
+	write!(write, "]")
+}
+
+fn write_indent(write: &mut dyn Write, depth: usize) -> Result<()> {
+	for _ in 0..depth {
+		write!(write, "\t")?;
+	}
+
+	Ok(())
+}
+
+/// A writer of the text format.
+///
+/// Nodes are written inside of the region they belong to, and the [`Argument`] node
+/// of every region is named in its header. Nodes outside of any region are written
+/// at the top level, starting with the root [`Omega`] node if there is one.
+/// Names are numbered in the order they are defined.
+///
+/// [`Argument`]: crate::collection::node::Argument
+/// [`Omega`]: crate::collection::node::Omega
+pub struct Printer {
+	region_finder: RegionFinder,
+
+	regions: HashMap<(Id, usize), Range<usize>>,
+	members: Vec<Id>,
+	claimed: Vec<bool>,
+
+	names: Vec<usize>,
+	written: Vec<bool>,
+	next: usize,
+}
+
+impl Printer {
+	/// Creates a new, reusable [`Printer`] instance.
+	#[inline]
+	#[must_use]
+	pub fn new() -> Self {
+		Self {
+			region_finder: RegionFinder::new(),
+
+			regions: HashMap::new(),
+			members: Vec::new(),
+			claimed: Vec::new(),
+
+			names: Vec::new(),
+			written: Vec::new(),
+			next: 0,
+		}
+	}
+
+	fn find_header<T>(nodes: &DataFlowGraph<T>, id: Id, region: usize) -> Option<Id> {
+		let argument = *nodes[id].as_arguments()?.get(region)?;
+
+		nodes.get(argument)?.as_argument().map(|_| argument)
+	}
+
+	fn find_regions<T: Parameters>(&mut self, nodes: &DataFlowGraph<T>) {
+		self.regions.clear();
+		self.members.clear();
+		self.claimed.clear();
+		self.claimed.resize(nodes.indices_needed(), false);
+
+		for (id, node) in nodes.iter() {
+			for region in 0..node.as_arguments().map_or(0, <[_]>::len) {
+				if let Some(argument) = Self::find_header(nodes, id, region) {
+					self.claimed[argument] = true;
+				}
+			}
+		}
+
+		// A node reachable from many regions is written in the first one found.
+		for (id, node) in nodes.iter() {
+			for region in 0..node.as_results().map_or(0, <[_]>::len) {
+				let start = self.members.len();
+
+				self.region_finder.run(nodes, id, region);
+
+				for &member in self.region_finder.nodes() {
+					if !self.claimed[member] {
+						self.claimed[member] = true;
+						self.members.push(member);
+					}
+				}
+
+				self.regions.insert((id, region), start..self.members.len());
+			}
+		}
+	}
+
+	fn name_node<T>(&mut self, nodes: &DataFlowGraph<T>, id: Id) {
+		if self.names[id] != UNNAMED {
+			return;
+		}
+
+		self.names[id] = self.next;
+		self.next += 1;
+
+		for region in 0..nodes[id].as_results().map_or(0, <[_]>::len) {
+			if let Some(argument) = Self::find_header(nodes, id, region) {
+				if self.names[argument] == UNNAMED {
+					self.names[argument] = self.next;
+					self.next += 1;
+				}
+			}
+
+			for index in self.regions[&(id, region)].clone() {
+				self.name_node(nodes, self.members[index]);
+			}
+		}
+	}
+
+	fn find_names<T>(&mut self, nodes: &DataFlowGraph<T>) {
+		self.names.clear();
+		self.names.resize(nodes.indices_needed(), UNNAMED);
+		self.next = 0;
+
+		if let Some(omega) = nodes.root() {
+			self.name_node(nodes, omega);
+		}
+
+		for (id, _) in nodes.iter() {
+			if !self.claimed[id] {
+				self.name_node(nodes, id);
+			}
+		}
+
+		// Nodes only claimed by each other's regions are left, so write them anyway.
+		for (id, node) in nodes.iter() {
+			if node.as_argument().is_none() {
+				self.name_node(nodes, id);
+			}
+		}
+	}
+
+	fn write_link<T>(
+		&self,
+		write: &mut dyn Write,
+		nodes: &DataFlowGraph<T>,
+		link: Link,
+	) -> Result<()> {
+		if nodes.get(link.node).is_some() {
+			write!(write, "%{}:{}", self.names[link.node], link.port)
+		} else {
+			write!(write, "_")
+		}
+	}
+
+	fn write_links<'a, T, I>(
+		&self,
+		write: &mut dyn Write,
+		nodes: &DataFlowGraph<T>,
+		links: I,
+	) -> Result<()>
+	where
+		I: IntoIterator<Item = &'a Link>,
+	{
+		write!(write, "(")?;
+
+		for (index, &link) in links.into_iter().enumerate() {
+			if index != 0 {
+				write!(write, ", ")?;
+			}
+
+			self.write_link(write, nodes, link)?;
+		}
+
+		write!(write, ")")
+	}
+
+	fn write_region<T>(
+		&mut self,
+		write: &mut dyn Write,
+		nodes: &DataFlowGraph<T>,
+		id: Id,
+		region: usize,
+		depth: usize,
+	) -> Result<()>
+	where
+		T: Parameters + Print,
+	{
+		write_indent(write, depth)?;
+
+		match Self::find_header(nodes, id, region) {
+			Some(argument) => writeln!(write, "region %{} {{", self.names[argument])?,
+			None => writeln!(write, "region _ {{")?,
+		}
+
+		for index in self.regions[&(id, region)].clone() {
+			self.write_node(write, nodes, self.members[index], depth + 1)?;
+		}
+
+		let results = &nodes[id].as_results().unwrap()[region];
+
+		write_indent(write, depth + 1)?;
+		write!(write, "results ")?;
+
+		self.write_links(write, nodes, results)?;
+
+		writeln!(write)?;
+		write_indent(write, depth)?;
+		writeln!(write, "}}")
+	}
+
+	fn write_node<T>(
+		&mut self,
+		write: &mut dyn Write,
+		nodes: &DataFlowGraph<T>,
+		id: Id,
+		depth: usize,
+	) -> Result<()>
+	where
+		T: Parameters + Print,
+	{
+		if self.written[id] {
+			return Ok(());
+		}
+
+		self.written[id] = true;
+
+		write_indent(write, depth)?;
+		write!(write, "%{} = ", self.names[id])?;
+
+		match &nodes[id] {
+			Node::Simple(simple) => {
+				simple.print(write)?;
+				write!(write, " ")?;
+
+				self.write_links(write, nodes, simple.parameters())?;
+
+				return writeln!(write);
+			}
+			Node::Argument(_) => return writeln!(write, "argument"),
+			Node::Gamma(_) => write!(write, "gamma ")?,
+			Node::Theta(_) => write!(write, "theta ")?,
+			Node::Phi(_) => write!(write, "phi ")?,
+			Node::Lambda(lambda) => write!(write, "lambda {} ", lambda.inputs)?,
+			Node::Delta(_) => write!(write, "delta ")?,
+			Node::Omega(omega) => {
+				write!(write, "omega ")?;
+				write_names(write, &omega.imports)?;
+				write!(write, " -> ")?;
+				write_names(write, &omega.exports)?;
+			}
+		}
+
+		if let Some(parameters) = nodes[id].as_parameters() {
+			self.write_links(write, nodes, parameters)?;
+		}
+
+		writeln!(write, " {{")?;
+
+		for region in 0..nodes[id].as_results().map_or(0, <[_]>::len) {
+			self.write_region(write, nodes, id, region, depth + 1)?;
+		}
+
+		write_indent(write, depth)?;
+		writeln!(write, "}}")
+	}
+
+	/// Writes the data flow graph to the writer in the text format.
+	///
+	/// # Errors
+	///
+	/// Returns an error if the writer fails to write.
+	pub fn write<T>(&mut self, write: &mut dyn Write, nodes: &DataFlowGraph<T>) -> Result<()>
+	where
+		T: Parameters + Print,
+	{
+		self.find_regions(nodes);
+		self.find_names(nodes);
+
+		self.written.clear();
+		self.written.resize(nodes.indices_needed(), false);
+
+		// The root is written first so that it is the first one read back.
+		if let Some(omega) = nodes.root() {
+			self.write_node(write, nodes, omega, 0)?;
+		}
+
+		for (id, _) in nodes.iter() {
+			if !self.claimed[id] {
+				self.write_node(write, nodes, id, 0)?;
+			}
+		}
+
+		for (id, node) in nodes.iter() {
+			if node.as_argument().is_none() {
+				self.write_node(write, nodes, id, 0)?;
+			}
+		}
+
+		Ok(())
+	}
+}
+
+impl Default for Printer {
+	#[inline]
+	fn default() -> Self {
+		Self::new()
+	}
+}
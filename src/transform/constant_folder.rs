@@ -0,0 +1,296 @@
+use crate::{
+	collection::{
+		data_flow_graph::DataFlowGraph,
+		link::{Id, Link},
+		node::{Arity, Node, Parameters, ParametersMut},
+	},
+	visit::depth_first_searcher::{DepthFirstSearcher, Event},
+};
+
+/// The result of folding a node.
+pub enum Replacement<T> {
+	/// The node is replaced in place by a new one with the same results.
+	Node(T),
+
+	/// The only result of the node is replaced by the link.
+	/// It is ignored for nodes with more than one result.
+	Link(Link),
+}
+
+/// A node that can be evaluated when some of its inputs are known.
+pub trait Fold: Parameters + Arity + Sized {
+	/// Returns whether the node is a constant, which has no parameters and a single result.
+	fn is_constant(&self) -> bool;
+
+	/// Returns a replacement for the node, or `None` if it cannot be folded.
+	/// Every input is the constant node behind the parameter at that index, if it is one.
+	fn fold(&self, inputs: &[Option<&Self>]) -> Option<Replacement<Self>>;
+}
+
+/// A constant folder.
+/// It folds simple nodes in post-order, revisiting the users of every folded node
+/// until nothing more can be folded.
+pub struct ConstantFolder {
+	depth_first_searcher: DepthFirstSearcher,
+
+	queue: Vec<Id>,
+	queued: Vec<bool>,
+}
+
+impl ConstantFolder {
+	/// Creates a new, reusable [`ConstantFolder`] instance.
+	#[inline]
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			depth_first_searcher: DepthFirstSearcher::new(),
+
+			queue: Vec::new(),
+			queued: Vec::new(),
+		}
+	}
+
+	fn find_replacement<T: Fold>(nodes: &DataFlowGraph<T>, id: Id) -> Option<Replacement<T>> {
+		let simple = nodes.get(id)?.as_simple()?;
+
+		if simple.is_constant() {
+			return None;
+		}
+
+		let inputs: Vec<_> = simple
+			.parameters()
+			.map(|link| {
+				nodes
+					.get(link.node)
+					.and_then(Node::as_simple)
+					.filter(|simple| simple.is_constant())
+			})
+			.collect();
+
+		// Only a single result can be redirected to the link.
+		simple.fold(&inputs).filter(|replacement| {
+			matches!(replacement, Replacement::Node(_)) || simple.arity() == 1
+		})
+	}
+
+	fn queue_users<T: Fold>(&mut self, nodes: &DataFlowGraph<T>, id: Id) {
+		for link in nodes.outputs(id) {
+			for user in nodes.users_of(link).iter() {
+				let user = user.node();
+
+				if !self.queued[user] && nodes[user].as_simple().is_some() {
+					self.queued[user] = true;
+					self.queue.push(user);
+				}
+			}
+		}
+	}
+
+	fn fold_node<T>(&mut self, nodes: &mut DataFlowGraph<T>, id: Id) -> bool
+	where
+		T: Fold + ParametersMut,
+	{
+		let Some(replacement) = Self::find_replacement(nodes, id) else {
+			return false;
+		};
+
+		self.queue_users(nodes, id);
+
+		match replacement {
+			Replacement::Node(simple) => {
+				nodes.replace_node(id, Node::Simple(simple));
+			}
+			Replacement::Link(link) => {
+				nodes.replace_uses(id.into(), link);
+				nodes.remove_node(id);
+			}
+		}
+
+		true
+	}
+
+	/// Folds every simple node reachable from the `start` node, including those of
+	/// nested regions, and returns the number of nodes folded.
+	pub fn run<T>(&mut self, nodes: &mut DataFlowGraph<T>, start: Id) -> usize
+	where
+		T: Fold + ParametersMut,
+	{
+		let tracked = nodes.users().is_some();
+
+		if !tracked {
+			nodes.track_users();
+		}
+
+		let active = self.depth_first_searcher.nodes_mut();
+
+		active.clear();
+		active.extend(0..nodes.indices_needed());
+
+		self.queue.clear();
+		self.queued.clear();
+		self.queued.resize(nodes.indices_needed(), false);
+
+		self.depth_first_searcher.run(nodes, start, |event| {
+			if let Event::PostNode { id } = event {
+				self.queue.push(id);
+			}
+		});
+
+		// Parameters are folded before their users.
+		self.queue.reverse();
+
+		for &id in &self.queue {
+			self.queued[id] = true;
+		}
+
+		let mut folded = 0;
+
+		while let Some(id) = self.queue.pop() {
+			self.queued[id] = false;
+
+			if self.fold_node(nodes, id) {
+				folded += 1;
+			}
+		}
+
+		if !tracked {
+			nodes.forget_users();
+		}
+
+		folded
+	}
+}
+
+impl Default for ConstantFolder {
+	#[inline]
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		collection::{
+			data_flow_graph::DataFlowGraph,
+			link::Link,
+			node::{Arity, Parameters, ParametersMut},
+		},
+		verify::verifier::Verifier,
+	};
+
+	use super::{ConstantFolder, Fold, Replacement};
+
+	enum Simple {
+		Constant(i64),
+		Add(Vec<Link>),
+		Split(Vec<Link>),
+	}
+
+	impl Parameters for Simple {
+		type Iter<'a> = std::slice::Iter<'a, Link>;
+
+		fn parameters(&self) -> Self::Iter<'_> {
+			match self {
+				Self::Constant(_) => [].iter(),
+				Self::Add(parameters) | Self::Split(parameters) => parameters.iter(),
+			}
+		}
+	}
+
+	impl ParametersMut for Simple {
+		type IterMut<'a> = std::slice::IterMut<'a, Link>;
+
+		fn parameters_mut(&mut self) -> Self::IterMut<'_> {
+			match self {
+				Self::Constant(_) => [].iter_mut(),
+				Self::Add(parameters) | Self::Split(parameters) => parameters.iter_mut(),
+			}
+		}
+	}
+
+	impl Arity for Simple {
+		fn arity(&self) -> usize {
+			if matches!(self, Self::Split(_)) {
+				2
+			} else {
+				1
+			}
+		}
+	}
+
+	impl Fold for Simple {
+		fn is_constant(&self) -> bool {
+			matches!(self, Self::Constant(_))
+		}
+
+		fn fold(&self, inputs: &[Option<&Self>]) -> Option<Replacement<Self>> {
+			let (Self::Add(parameters) | Self::Split(parameters)) = self else {
+				return None;
+			};
+
+			if matches!(self, Self::Split(_)) {
+				return Some(Replacement::Link(parameters[0]));
+			}
+
+			match inputs {
+				[Some(Self::Constant(lhs)), Some(Self::Constant(rhs))] => {
+					Some(Replacement::Node(Self::Constant(lhs + rhs)))
+				}
+				[_, Some(Self::Constant(0))] => Some(Replacement::Link(parameters[0])),
+				_ => None,
+			}
+		}
+	}
+
+	#[test]
+	fn test_folds_to_fixpoint() {
+		let mut nodes = DataFlowGraph::<Simple>::new();
+
+		let lambda = nodes.lambda(Vec::new(), 1, |lambda| {
+			let input = lambda.input(0);
+			let constant_0 = lambda.add_simple(Simple::Constant(1));
+			let constant_1 = lambda.add_simple(Simple::Constant(-1));
+			let add_0 = lambda.add_simple(Simple::Add(vec![constant_0, constant_1]));
+			let add_1 = lambda.add_simple(Simple::Add(vec![input, add_0]));
+			let add_2 = lambda.add_simple(Simple::Add(vec![add_1, constant_0]));
+
+			vec![add_1, add_2]
+		});
+
+		assert_eq!(ConstantFolder::new().run(&mut nodes, lambda.node), 2);
+
+		let lambda = nodes[lambda.node].as_lambda().unwrap();
+		let input = Link {
+			node: lambda.argument,
+			port: 0,
+		};
+
+		assert_eq!(lambda.results[0], input);
+		assert_eq!(
+			nodes[lambda.results[1].node].parameters().next(),
+			Some(&input)
+		);
+		assert!(nodes.users().is_none());
+	}
+
+	#[test]
+	fn test_keeps_nodes_with_many_results() {
+		let mut nodes = DataFlowGraph::<Simple>::new();
+
+		let lambda = nodes.lambda(Vec::new(), 1, |lambda| {
+			let input = lambda.input(0);
+			let split = lambda.add_simple(Simple::Split(vec![input]));
+
+			vec![split, Link { port: 1, ..split }]
+		});
+
+		assert_eq!(ConstantFolder::new().run(&mut nodes, lambda.node), 0);
+
+		let results = &nodes[lambda.node].as_lambda().unwrap().results;
+
+		assert_eq!(results[1].port, 1);
+		assert!(nodes[results[1].node].as_simple().is_some());
+		assert!(Verifier::new().run(&nodes).is_empty());
+	}
+}
@@ -0,0 +1,225 @@
+use crate::{
+	collection::{
+		data_flow_graph::DataFlowGraph,
+		link::{Id, Link},
+		node::{Parameters, ParametersMut},
+	},
+	visit::{dependency_finder::DependencyFinder, region_finder::RegionFinder},
+};
+
+/// A [`Gamma`] node fuser.
+/// It merges [`Gamma`] nodes of a region that share their predicate [`Link`]
+/// into one node with the outputs of both, if neither depends on the other.
+///
+/// [`Gamma`]: crate::collection::node::Gamma
+pub struct GammaFuser {
+	region_finder: RegionFinder,
+	dependency_finder: DependencyFinder,
+
+	gammas: Vec<Id>,
+}
+
+impl GammaFuser {
+	/// Creates a new, reusable [`GammaFuser`] instance.
+	#[inline]
+	#[must_use]
+	pub fn new() -> Self {
+		Self {
+			region_finder: RegionFinder::new(),
+			dependency_finder: DependencyFinder::new(),
+
+			gammas: Vec::new(),
+		}
+	}
+
+	fn can_fuse<T: Parameters>(&mut self, nodes: &DataFlowGraph<T>, first: Id, second: Id) -> bool {
+		let (Some(lhs), Some(rhs)) = (nodes[first].as_gamma(), nodes[second].as_gamma()) else {
+			return false;
+		};
+
+		lhs.parameters.last().is_some()
+			&& lhs.parameters.last() == rhs.parameters.last()
+			&& lhs.results.len() == rhs.results.len()
+			&& !self.dependency_finder.run(nodes, first, second)
+			&& !self.dependency_finder.run(nodes, second, first)
+	}
+
+	fn fuse<T>(nodes: &mut DataFlowGraph<T>, first: Id, second: Id)
+	where
+		T: Parameters + ParametersMut,
+	{
+		let gamma = nodes[second].as_gamma().unwrap();
+		let inputs = gamma.parameters[..gamma.parameters.len() - 1].to_vec();
+		let arguments = gamma.arguments.to_vec();
+		let targets = nodes[first].as_gamma().unwrap().arguments.to_vec();
+		let outputs = nodes[first]
+			.as_gamma()
+			.unwrap()
+			.results
+			.first()
+			.map_or(0, Vec::len);
+
+		for (index, link) in inputs.into_iter().enumerate() {
+			let port = nodes.add_parameter(first, link);
+
+			for (&argument, &target) in arguments.iter().zip(&targets) {
+				let from = Link {
+					node: argument,
+					port: index.try_into().unwrap(),
+				};
+				let to = Link { node: target, port };
+
+				nodes.replace_uses(from, to);
+			}
+		}
+
+		let results = nodes[second].as_gamma().unwrap().results.to_vec();
+
+		for port in 0..results.first().map_or(0, Vec::len) {
+			let from = Link {
+				node: second,
+				port: port.try_into().unwrap(),
+			};
+			let to = Link {
+				node: first,
+				port: (outputs + port).try_into().unwrap(),
+			};
+
+			nodes.replace_uses(from, to);
+		}
+
+		nodes.modify(first, |node| {
+			let gamma = node.as_mut_gamma().unwrap();
+
+			for (list, results) in gamma.results.iter_mut().zip(results) {
+				list.extend(results);
+			}
+		});
+
+		nodes.remove_node(second);
+
+		for argument in arguments {
+			nodes.remove_node(argument);
+		}
+	}
+
+	/// Fuses the [`Gamma`] nodes of the region `region` of the `parent` node
+	/// and returns the number of nodes removed.
+	///
+	/// [`Gamma`]: crate::collection::node::Gamma
+	pub fn run<T>(&mut self, nodes: &mut DataFlowGraph<T>, parent: Id, region: usize) -> usize
+	where
+		T: Parameters + ParametersMut,
+	{
+		self.region_finder.run(nodes, parent, region);
+
+		self.gammas.clear();
+		self.gammas.extend(
+			self.region_finder
+				.nodes()
+				.iter()
+				.filter(|&&id| nodes[id].as_gamma().is_some()),
+		);
+
+		let mut fused = 0;
+
+		for index in 1..self.gammas.len() {
+			let second = self.gammas[index];
+
+			for earlier in 0..index {
+				let first = self.gammas[earlier];
+
+				if nodes.get(first).is_some() && self.can_fuse(nodes, first, second) {
+					Self::fuse(nodes, first, second);
+
+					fused += 1;
+
+					break;
+				}
+			}
+		}
+
+		fused
+	}
+}
+
+impl Default for GammaFuser {
+	#[inline]
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		collection::{data_flow_graph::DataFlowGraph, link::Link},
+		fixture::Simple,
+	};
+
+	use super::GammaFuser;
+
+	#[test]
+	fn test_fuses_independent_gammas() {
+		let mut nodes = DataFlowGraph::<Simple>::new();
+		let mut gammas = Vec::new();
+
+		let lambda = nodes.lambda(Vec::new(), 2, |lambda| {
+			let predicate = lambda.input(0);
+			let input = lambda.input(1);
+
+			let gamma_0 = lambda.gamma(predicate, vec![input], 2, |gamma| {
+				let input = gamma.input(0);
+
+				vec![gamma.add_simple(Simple(vec![input]))]
+			});
+			let gamma_1 = lambda.gamma(predicate, vec![input], 2, |gamma| vec![gamma.input(0)]);
+			let gamma_2 = lambda.gamma(predicate, vec![gamma_0], 2, |gamma| vec![gamma.input(0)]);
+
+			gammas.extend([gamma_0, gamma_1, gamma_2]);
+
+			vec![gamma_0, gamma_1, gamma_2]
+		});
+
+		assert_eq!(GammaFuser::new().run(&mut nodes, lambda.node, 0), 1);
+		assert!(nodes.get(gammas[1].node).is_none());
+
+		let gamma = nodes[gammas[0].node].as_gamma().unwrap();
+		let results = &nodes[lambda.node].as_lambda().unwrap().results;
+		let fused = Link {
+			node: gammas[0].node,
+			port: 1,
+		};
+
+		assert_eq!(results, &[gammas[0], fused, gammas[2]]);
+		assert_eq!(gamma.parameters.len(), 3);
+
+		for (&argument, list) in gamma.arguments.iter().zip(gamma.results.iter()) {
+			let input = Link {
+				node: argument,
+				port: 1,
+			};
+
+			assert_eq!(list[1], input);
+		}
+	}
+
+	#[test]
+	fn test_keeps_gammas_used_by_regions() {
+		let mut nodes = DataFlowGraph::<Simple>::new();
+
+		let lambda = nodes.lambda(Vec::new(), 2, |lambda| {
+			let predicate = lambda.input(0);
+			let input = lambda.input(1);
+
+			let gamma_0 = lambda.gamma(predicate, vec![input], 2, |gamma| vec![gamma.input(0)]);
+			let gamma_1 = lambda.gamma(predicate, vec![input], 2, |gamma| {
+				vec![gamma.add_simple(Simple(vec![gamma_0]))]
+			});
+
+			vec![gamma_0, gamma_1]
+		});
+
+		assert_eq!(GammaFuser::new().run(&mut nodes, lambda.node, 0), 0);
+	}
+}
@@ -0,0 +1,530 @@
+use std::collections::HashMap;
+
+use crate::collection::{
+	data_flow_graph::DataFlowGraph,
+	link::{Id, Link},
+	node::{Node, Parameters, ParametersMut},
+};
+
+use super::value_numberer::Value;
+
+/// A node that may be a known predicate.
+pub trait Predicate: Parameters {
+	/// Returns the index of the region chosen by the result at `port` of the node
+	/// if it is a constant predicate.
+	fn as_predicate(&self, port: u16) -> Option<usize>;
+
+	/// Returns a node that chooses region `regions[index]` where `predicate` chooses
+	/// region `index`, or `None` if there is no such node. Without it, regions are
+	/// only merged when all of them are equal.
+	fn remap(_predicate: Link, _regions: Vec<usize>) -> Option<Self>
+	where
+		Self: Sized,
+	{
+		None
+	}
+}
+
+/// A [`Gamma`] node simplifier.
+/// It removes the node when its predicate is known or all of its regions are equal,
+/// merges regions equal to an earlier one through [`Predicate::remap`],
+/// and forwards outputs that pass the same parameter through every region.
+///
+/// [`Gamma`]: crate::collection::node::Gamma
+pub struct GammaSimplifier {
+	pairs: HashMap<Id, Id>,
+	stack: Vec<(Link, Link)>,
+}
+
+impl GammaSimplifier {
+	/// Creates a new, reusable [`GammaSimplifier`] instance.
+	#[inline]
+	#[must_use]
+	pub fn new() -> Self {
+		Self {
+			pairs: HashMap::new(),
+			stack: Vec::new(),
+		}
+	}
+
+	fn find_chosen<T: Predicate>(nodes: &DataFlowGraph<T>, id: Id) -> Option<usize> {
+		let gamma = nodes[id].as_gamma()?;
+		let predicate = gamma.parameters.last()?;
+		let simple = nodes.get(predicate.node)?.as_simple()?;
+		let region = simple.as_predicate(predicate.port)?;
+
+		(region < gamma.results.len()).then_some(region)
+	}
+
+	fn is_pair_equal<T: Value>(&mut self, nodes: &DataFlowGraph<T>, lhs: Link, rhs: Link) -> bool {
+		if lhs == rhs {
+			return true;
+		}
+
+		if lhs.port != rhs.port {
+			return false;
+		}
+
+		if let Some(&other) = self.pairs.get(&lhs.node) {
+			return other == rhs.node;
+		}
+
+		let (Some(Node::Simple(left)), Some(Node::Simple(right))) =
+			(nodes.get(lhs.node), nodes.get(rhs.node))
+		else {
+			return false;
+		};
+
+		let (Some(left_key), Some(right_key)) = (left.key(), right.key()) else {
+			return false;
+		};
+
+		if left_key != right_key || left.parameters().count() != right.parameters().count() {
+			return false;
+		}
+
+		self.pairs.insert(lhs.node, rhs.node);
+		self.stack
+			.extend(left.parameters().copied().zip(right.parameters().copied()));
+
+		true
+	}
+
+	// Regions are equal if their results are computed by pairs of equal nodes,
+	// with the arguments of the regions paired up front.
+	fn is_region_equal<T: Value>(
+		&mut self,
+		nodes: &DataFlowGraph<T>,
+		id: Id,
+		lhs: usize,
+		rhs: usize,
+	) -> bool {
+		let gamma = nodes[id].as_gamma().unwrap();
+
+		self.pairs.clear();
+		self.pairs
+			.insert(gamma.arguments[lhs], gamma.arguments[rhs]);
+
+		self.stack.clear();
+		self.stack.extend(
+			gamma.results[lhs]
+				.iter()
+				.copied()
+				.zip(gamma.results[rhs].iter().copied()),
+		);
+
+		while let Some((lhs, rhs)) = self.stack.pop() {
+			if !self.is_pair_equal(nodes, lhs, rhs) {
+				return false;
+			}
+		}
+
+		true
+	}
+
+	// Every region is numbered after the first region equal to it.
+	fn find_classes<T: Value>(&mut self, nodes: &DataFlowGraph<T>, id: Id) -> Vec<usize> {
+		let regions = nodes[id].as_gamma().unwrap().results.len();
+		let mut firsts = Vec::new();
+
+		(0..regions)
+			.map(|region| {
+				let class = firsts
+					.iter()
+					.position(|&first| self.is_region_equal(nodes, id, first, region));
+
+				class.unwrap_or_else(|| {
+					firsts.push(region);
+
+					firsts.len() - 1
+				})
+			})
+			.collect()
+	}
+
+	// Regions equal to an earlier one are removed and the predicate is remapped to match.
+	fn merge_regions<T>(nodes: &mut DataFlowGraph<T>, id: Id, classes: Vec<usize>) -> bool
+	where
+		T: Predicate + ParametersMut,
+	{
+		let gamma = nodes[id].as_gamma().unwrap();
+		let predicate = *gamma.parameters.last().unwrap();
+		let keep: Vec<_> = (0..classes.len())
+			.map(|region| !classes[..region].contains(&classes[region]))
+			.collect();
+
+		let Some(select) = T::remap(predicate, classes) else {
+			return false;
+		};
+
+		let select = nodes.add_simple(select);
+
+		for region in (0..keep.len()).filter(|&region| !keep[region]) {
+			nodes.remove_subgraph(id, region);
+		}
+
+		let arguments = nodes.modify(id, |node| {
+			let gamma = node.as_mut_gamma().unwrap();
+
+			gamma.arguments = keep
+				.iter()
+				.zip(gamma.arguments.iter())
+				.filter(|v| *v.0)
+				.map(|v| *v.1)
+				.collect();
+			gamma.results = keep
+				.iter()
+				.zip(gamma.results.iter())
+				.filter(|v| *v.0)
+				.map(|v| v.1.clone())
+				.collect();
+			*gamma.parameters.last_mut().unwrap() = select;
+
+			gamma.arguments.to_vec()
+		});
+
+		for (region, argument) in arguments.into_iter().enumerate() {
+			nodes.modify(argument, |node| {
+				node.as_mut_argument().unwrap().region = region
+			});
+		}
+
+		true
+	}
+
+	/// Replaces the node with the region, removing the nodes of every other region.
+	pub(crate) fn inline_region<T>(nodes: &mut DataFlowGraph<T>, id: Id, region: usize)
+	where
+		T: Parameters + ParametersMut,
+	{
+		let gamma = nodes[id].as_gamma().unwrap();
+		let argument = gamma.arguments[region];
+		let regions = gamma.results.len();
+		let parameters = gamma.parameters.clone();
+
+		for (port, &link) in parameters
+			.iter()
+			.take(parameters.len().saturating_sub(1))
+			.enumerate()
+		{
+			let from = Link {
+				node: argument,
+				port: port.try_into().unwrap(),
+			};
+
+			nodes.replace_uses(from, link);
+		}
+
+		let results = nodes[id].as_gamma().unwrap().results[region].clone();
+
+		for (port, link) in results.into_iter().enumerate() {
+			let from = Link {
+				node: id,
+				port: port.try_into().unwrap(),
+			};
+
+			nodes.replace_uses(from, link);
+		}
+
+		for other in (0..regions).filter(|&other| other != region) {
+			nodes.remove_subgraph(id, other);
+		}
+
+		nodes.remove_node(id);
+		nodes.remove_node(argument);
+	}
+
+	fn forward_outputs<T>(nodes: &mut DataFlowGraph<T>, id: Id) -> bool
+	where
+		T: Parameters + ParametersMut,
+	{
+		let gamma = nodes[id].as_gamma().unwrap();
+		let inputs = gamma.parameters.len().saturating_sub(1);
+		let outputs = gamma.results.first().map_or(0, Vec::len);
+
+		let forwarded: Vec<_> = (0..outputs)
+			.filter_map(|index| {
+				let port = gamma.results[0][index].port;
+				let is_forwarded = gamma
+					.arguments
+					.iter()
+					.zip(gamma.results.iter())
+					.all(|(&node, results)| results[index] == Link { node, port });
+
+				let from = Link {
+					node: id,
+					port: index.try_into().unwrap(),
+				};
+
+				(is_forwarded && usize::from(port) < inputs)
+					.then(|| (from, gamma.parameters[usize::from(port)]))
+			})
+			.collect();
+
+		let mut changed = false;
+
+		for (from, to) in forwarded {
+			changed |= nodes.replace_uses(from, to) != 0;
+		}
+
+		changed
+	}
+
+	/// Simplifies the [`Gamma`] node, returning whether anything changed.
+	///
+	/// The nodes of regions that are not kept are removed along with them.
+	///
+	/// [`Gamma`]: crate::collection::node::Gamma
+	pub fn run<T>(&mut self, nodes: &mut DataFlowGraph<T>, id: Id) -> bool
+	where
+		T: Predicate + Value + ParametersMut,
+	{
+		if nodes.get(id).and_then(Node::as_gamma).is_none() {
+			return false;
+		}
+
+		if let Some(region) = Self::find_chosen(nodes, id) {
+			Self::inline_region(nodes, id, region);
+
+			return true;
+		}
+
+		let classes = self.find_classes(nodes, id);
+		let count = classes.iter().max().map_or(0, |&class| class + 1);
+
+		if count == 1 {
+			Self::inline_region(nodes, id, 0);
+
+			return true;
+		}
+
+		let merged = count < classes.len() && Self::merge_regions(nodes, id, classes);
+
+		Self::forward_outputs(nodes, id) || merged
+	}
+}
+
+impl Default for GammaSimplifier {
+	#[inline]
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		collection::{
+			data_flow_graph::DataFlowGraph,
+			link::Link,
+			node::{Arity, Parameters, ParametersMut},
+		},
+		transform::value_numberer::Value,
+		verify::verifier::Verifier,
+	};
+
+	use super::{GammaSimplifier, Predicate};
+
+	enum Simple {
+		Constant(usize),
+		Add(Vec<Link>),
+		Select(Link, Vec<usize>),
+		Pair(usize, usize),
+	}
+
+	impl Parameters for Simple {
+		type Iter<'a> = std::slice::Iter<'a, Link>;
+
+		fn parameters(&self) -> Self::Iter<'_> {
+			match self {
+				Self::Constant(_) | Self::Pair(..) => [].iter(),
+				Self::Add(parameters) => parameters.iter(),
+				Self::Select(predicate, _) => std::slice::from_ref(predicate).iter(),
+			}
+		}
+	}
+
+	impl ParametersMut for Simple {
+		type IterMut<'a> = std::slice::IterMut<'a, Link>;
+
+		fn parameters_mut(&mut self) -> Self::IterMut<'_> {
+			match self {
+				Self::Constant(_) | Self::Pair(..) => [].iter_mut(),
+				Self::Add(parameters) => parameters.iter_mut(),
+				Self::Select(predicate, _) => std::slice::from_mut(predicate).iter_mut(),
+			}
+		}
+	}
+
+	impl Arity for Simple {
+		fn arity(&self) -> usize {
+			if matches!(self, Self::Pair(..)) {
+				2
+			} else {
+				1
+			}
+		}
+	}
+
+	impl Value for Simple {
+		type Key = Option<usize>;
+
+		fn key(&self) -> Option<Self::Key> {
+			match self {
+				Self::Constant(value) => Some(Some(*value)),
+				Self::Add(_) => Some(None),
+				Self::Select(..) | Self::Pair(..) => None,
+			}
+		}
+	}
+
+	impl Predicate for Simple {
+		fn as_predicate(&self, port: u16) -> Option<usize> {
+			match (self, port) {
+				(Self::Constant(value), 0) | (Self::Pair(value, _), 0) => Some(*value),
+				(Self::Pair(_, value), 1) => Some(*value),
+				_ => None,
+			}
+		}
+
+		fn remap(predicate: Link, regions: Vec<usize>) -> Option<Self> {
+			Some(Self::Select(predicate, regions))
+		}
+	}
+
+	#[test]
+	fn test_inlines_chosen_region() {
+		let mut nodes = DataFlowGraph::<Simple>::new();
+
+		let input = nodes.add_simple(Simple::Add(Vec::new()));
+		let predicate = nodes.add_simple(Simple::Constant(1));
+		let gamma = nodes.gamma(predicate, vec![input], 2, |gamma| {
+			let input = gamma.input(0);
+
+			if gamma.index() == 0 {
+				vec![input]
+			} else {
+				vec![gamma.add_simple(Simple::Add(vec![input, input]))]
+			}
+		});
+		let user = nodes.add_simple(Simple::Add(vec![gamma]));
+
+		assert!(GammaSimplifier::new().run(&mut nodes, gamma.node));
+		assert!(nodes.get(gamma.node).is_none());
+
+		let add = *nodes[user.node].parameters().next().unwrap();
+
+		assert_eq!(
+			nodes[add.node].parameters().copied().collect::<Vec<_>>(),
+			[input, input]
+		);
+		assert!(Verifier::new().run(&nodes).is_empty());
+	}
+
+	#[test]
+	fn test_inlines_region_chosen_by_port() {
+		let mut nodes = DataFlowGraph::<Simple>::new();
+
+		let input = nodes.add_simple(Simple::Add(Vec::new()));
+		let pair = nodes.add_simple(Simple::Pair(0, 1));
+		let predicate = Link { port: 1, ..pair };
+		let gamma = nodes.gamma(predicate, vec![input], 2, |gamma| {
+			let input = gamma.input(0);
+
+			if gamma.index() == 0 {
+				vec![input]
+			} else {
+				vec![gamma.add_simple(Simple::Add(vec![input]))]
+			}
+		});
+		let user = nodes.add_simple(Simple::Add(vec![gamma]));
+
+		assert!(GammaSimplifier::new().run(&mut nodes, gamma.node));
+		assert!(nodes.get(gamma.node).is_none());
+
+		let add = *nodes[user.node].parameters().next().unwrap();
+
+		assert_ne!(add, input);
+		assert_eq!(nodes[add.node].parameters().next(), Some(&input));
+		assert!(Verifier::new().run(&nodes).is_empty());
+	}
+
+	#[test]
+	fn test_merges_equal_regions() {
+		let mut nodes = DataFlowGraph::<Simple>::new();
+
+		let input = nodes.add_simple(Simple::Add(Vec::new()));
+		let predicate = nodes.add_simple(Simple::Add(Vec::new()));
+		let gamma_0 = nodes.gamma(predicate, vec![input, predicate], 2, |gamma| {
+			let input = gamma.input(0);
+			let add = gamma.add_simple(Simple::Add(vec![input]));
+
+			vec![
+				gamma.input(1),
+				gamma.add_simple(Simple::Add(vec![add, add])),
+			]
+		});
+		let gamma_1 = nodes.gamma(predicate, vec![input], 2, |gamma| {
+			let input = gamma.input(0);
+
+			let index = gamma.index();
+
+			vec![input, gamma.add_simple(Simple::Constant(index))]
+		});
+		let user = nodes.add_simple(Simple::Add(vec![
+			gamma_0,
+			Link {
+				node: gamma_0.node,
+				port: 1,
+			},
+			gamma_1,
+		]));
+
+		let mut simplifier = GammaSimplifier::new();
+
+		assert!(simplifier.run(&mut nodes, gamma_0.node));
+		assert!(simplifier.run(&mut nodes, gamma_1.node));
+		assert!(!simplifier.run(&mut nodes, gamma_1.node));
+		assert!(nodes.get(gamma_0.node).is_none());
+
+		let parameters: Vec<_> = nodes[user.node].parameters().copied().collect();
+
+		assert_eq!(parameters[0], predicate);
+		assert_eq!(parameters[2], input);
+		assert_eq!(nodes[parameters[1].node].parameters().count(), 2);
+		assert!(Verifier::new().run(&nodes).is_empty());
+	}
+
+	#[test]
+	fn test_merges_equal_subsets() {
+		let mut nodes = DataFlowGraph::<Simple>::new();
+
+		let input = nodes.add_simple(Simple::Add(Vec::new()));
+		let predicate = nodes.add_simple(Simple::Add(Vec::new()));
+		let gamma = nodes.gamma(predicate, vec![input], 3, |gamma| {
+			let input = gamma.input(0);
+
+			if gamma.index() == 1 {
+				vec![input]
+			} else {
+				vec![gamma.add_simple(Simple::Add(vec![input, input]))]
+			}
+		});
+		let count = nodes.iter().count();
+
+		assert!(GammaSimplifier::new().run(&mut nodes, gamma.node));
+
+		let node = nodes[gamma.node].as_gamma().unwrap();
+		let select = *node.parameters.last().unwrap();
+
+		assert_eq!(node.results.len(), 2);
+		assert!(matches!(
+			&nodes[select.node].as_simple(),
+			Some(Simple::Select(link, regions)) if *link == predicate && regions == &[0, 1, 0]
+		));
+
+		// The argument and addition of the last region are gone, the select is new.
+		assert_eq!(nodes.iter().count(), count - 1);
+		assert!(Verifier::new().run(&nodes).is_empty());
+	}
+}
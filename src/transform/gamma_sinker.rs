@@ -0,0 +1,225 @@
+use crate::collection::{
+	data_flow_graph::DataFlowGraph,
+	link::{Id, Link},
+	node::{Arity, Node, Parameters, ParametersMut},
+	users::Use,
+};
+
+use super::{port_pruner::PortPruner, value_numberer::Value};
+
+/// A [`Gamma`] node sinker.
+/// It moves simple nodes that are only used by one region of a [`Gamma`] node
+/// into that region, so that they are not computed when another region is chosen.
+///
+/// Only nodes with a [`Value::key`] are moved, as those are free of side effects.
+///
+/// [`Gamma`]: crate::collection::node::Gamma
+pub struct GammaSinker {
+	fed: Vec<(usize, u16)>,
+}
+
+impl GammaSinker {
+	/// Creates a new, reusable [`GammaSinker`] instance.
+	#[inline]
+	#[must_use]
+	pub const fn new() -> Self {
+		Self { fed: Vec::new() }
+	}
+
+	// Finds the inputs fed by the node, if it is used by nothing else.
+	fn find_fed<T>(&mut self, nodes: &DataFlowGraph<T>, id: Id, node: Id) -> bool
+	where
+		T: Value + Arity,
+	{
+		let inputs = nodes[id].as_gamma().unwrap().parameters.len() - 1;
+
+		self.fed.clear();
+
+		for link in nodes.outputs(node) {
+			for &user in nodes.users_of(link).iter() {
+				match user {
+					Use::Parameter { node, index } if node == id && index < inputs => {
+						self.fed.push((index, link.port));
+					}
+					_ => return false,
+				}
+			}
+		}
+
+		true
+	}
+
+	fn find_region<T: Parameters>(&self, nodes: &DataFlowGraph<T>, id: Id) -> Option<usize> {
+		let arguments = &nodes[id].as_gamma().unwrap().arguments;
+		let mut used = arguments.iter().enumerate().filter(|(_, &argument)| {
+			self.fed.iter().any(|&(index, _)| {
+				let link = Link {
+					node: argument,
+					port: index.try_into().unwrap(),
+				};
+
+				!nodes.users_of(link).is_empty()
+			})
+		});
+
+		let (region, _) = used.next()?;
+
+		used.next().is_none().then_some(region)
+	}
+
+	fn find_sinkable<T>(&mut self, nodes: &DataFlowGraph<T>, id: Id) -> Option<(Id, usize)>
+	where
+		T: Value + Arity,
+	{
+		let gamma = nodes[id].as_gamma().unwrap();
+		let inputs = gamma.parameters.len() - 1;
+
+		gamma.parameters[..inputs].iter().find_map(|link| {
+			let simple = nodes.get(link.node)?.as_simple()?;
+
+			simple.key()?;
+
+			if !self.find_fed(nodes, id, link.node) {
+				return None;
+			}
+
+			self.find_region(nodes, id)
+				.map(|region| (link.node, region))
+		})
+	}
+
+	fn sink<T>(&self, nodes: &mut DataFlowGraph<T>, id: Id, node: Id, region: usize)
+	where
+		T: Value + ParametersMut,
+	{
+		let argument = nodes[id].as_gamma().unwrap().arguments[region];
+		let parameters: Vec<_> = nodes[node].parameters().copied().collect();
+
+		// Values already passed in are used again.
+		let parameters: Vec<_> = parameters
+			.into_iter()
+			.map(|link| {
+				let gamma = nodes[id].as_gamma().unwrap();
+				let inputs = &gamma.parameters[..gamma.parameters.len() - 1];
+				let port = inputs.iter().position(|&input| input == link).map_or_else(
+					|| nodes.add_parameter(id, link),
+					|index| index.try_into().unwrap(),
+				);
+
+				Link {
+					node: argument,
+					port,
+				}
+			})
+			.collect();
+
+		nodes.modify(node, |node| {
+			for (link, parameter) in node.parameters_mut().zip(parameters) {
+				*link = parameter;
+			}
+		});
+
+		for &(index, port) in &self.fed {
+			let from = Link {
+				node: argument,
+				port: index.try_into().unwrap(),
+			};
+
+			nodes.replace_uses(from, Link { node, port });
+		}
+	}
+
+	/// Moves every node only used by one region of the [`Gamma`] node into that region,
+	/// including those only used by nodes that were moved, and returns how many were moved.
+	/// Inputs of the [`Gamma`] node that are no longer used are removed.
+	///
+	/// [`Gamma`]: crate::collection::node::Gamma
+	pub fn run<T>(&mut self, nodes: &mut DataFlowGraph<T>, id: Id) -> usize
+	where
+		T: Value + Arity + ParametersMut,
+	{
+		if nodes.get(id).and_then(Node::as_gamma).is_none() {
+			return 0;
+		}
+
+		let tracked = nodes.users().is_some();
+
+		if !tracked {
+			nodes.track_users();
+		}
+
+		let mut sunk = 0;
+
+		while let Some((node, region)) = self.find_sinkable(nodes, id) {
+			self.sink(nodes, id, node, region);
+
+			PortPruner::prune_gamma(nodes, id, true);
+
+			sunk += 1;
+		}
+
+		if !tracked {
+			nodes.forget_users();
+		}
+
+		sunk
+	}
+}
+
+impl Default for GammaSinker {
+	#[inline]
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		collection::{data_flow_graph::DataFlowGraph, link::Link},
+		fixture::Named,
+		verify::verifier::Verifier,
+	};
+
+	use super::GammaSinker;
+
+	#[test]
+	fn test_sinks_into_used_region() {
+		let mut nodes = DataFlowGraph::<Named>::new();
+
+		let input = nodes.add_simple(Named("print", Vec::new()));
+		let add_0 = nodes.add_simple(Named("add", vec![input]));
+		let add_1 = nodes.add_simple(Named("add", vec![add_0, add_0]));
+		let print = nodes.add_simple(Named("print", vec![input]));
+		let shared = nodes.add_simple(Named("add", vec![input]));
+		let gamma = nodes.gamma(input, vec![add_1, print, shared], 2, |gamma| {
+			if gamma.index() == 0 {
+				vec![gamma.input(2)]
+			} else {
+				let inputs = vec![gamma.input(0), gamma.input(1)];
+				let shared = gamma.input(2);
+				let add = gamma.add_simple(Named("add", inputs));
+
+				vec![gamma.add_simple(Named("add", vec![add, shared]))]
+			}
+		});
+		let user = nodes.add_simple(Named("print", vec![gamma, shared]));
+
+		assert_eq!(GammaSinker::new().run(&mut nodes, gamma.node), 2);
+		assert!(nodes.users().is_none());
+
+		let gamma = nodes[gamma.node].as_gamma().unwrap();
+		let argument = Link {
+			node: gamma.arguments[1],
+			port: 2,
+		};
+
+		assert_eq!(gamma.parameters, [print, shared, input, input]);
+		assert_eq!(
+			nodes[add_0.node].parameters().copied().collect::<Vec<_>>(),
+			[argument]
+		);
+		assert_eq!(nodes[user.node].parameters().nth(1), Some(&shared));
+		assert!(Verifier::new().run(&nodes).is_empty());
+	}
+}
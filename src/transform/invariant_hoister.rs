@@ -0,0 +1,212 @@
+use std::collections::HashSet;
+
+use crate::{
+	collection::{
+		data_flow_graph::DataFlowGraph,
+		link::{Id, Link},
+		node::{Arity, Node, Parameters, ParametersMut},
+		users::Use,
+	},
+	visit::region_finder::RegionFinder,
+};
+
+use super::value_numberer::Value;
+
+/// A loop-invariant code mover.
+/// It moves simple nodes out of a [`Theta`] node when their inputs are the same
+/// for every iteration, passing their results back in as new loop variables.
+///
+/// Only nodes with a [`Value::key`] are moved, as those are free of side effects.
+///
+/// [`Theta`]: crate::collection::node::Theta
+pub struct InvariantHoister {
+	region_finder: RegionFinder,
+
+	hoisted: HashSet<Id>,
+	order: Vec<Id>,
+}
+
+impl InvariantHoister {
+	/// Creates a new, reusable [`InvariantHoister`] instance.
+	#[inline]
+	#[must_use]
+	pub fn new() -> Self {
+		Self {
+			region_finder: RegionFinder::new(),
+
+			hoisted: HashSet::new(),
+			order: Vec::new(),
+		}
+	}
+
+	fn is_invariant<T: Value>(&self, nodes: &DataFlowGraph<T>, theta: Id, id: Id) -> bool {
+		let theta = nodes[theta].as_theta().unwrap();
+		let Some(simple) = nodes[id].as_simple() else {
+			return false;
+		};
+
+		// Nodes outside of the region do not depend on the loop at all.
+		simple.key().is_some()
+			&& simple.parameters().all(|link| {
+				self.hoisted.contains(&link.node)
+					|| !self.region_finder.contains(link.node)
+					|| (link.node == theta.argument && theta.is_invariant(link.port.into()))
+			})
+	}
+
+	fn find_invariants<T: Value>(&mut self, nodes: &DataFlowGraph<T>, id: Id) {
+		self.hoisted.clear();
+		self.order.clear();
+
+		self.region_finder.run(nodes, id, 0);
+
+		// Members come after their parameters, so those are already decided.
+		for &member in self.region_finder.nodes() {
+			if self.is_invariant(nodes, id, member) {
+				self.hoisted.insert(member);
+				self.order.push(member);
+			}
+		}
+	}
+
+	fn is_used_inside<T: Parameters>(&self, nodes: &DataFlowGraph<T>, id: Id, link: Link) -> bool {
+		nodes.users_of(link).iter().any(|&user| match user {
+			Use::Parameter { node, .. } => {
+				self.region_finder.contains(node) && !self.hoisted.contains(&node)
+			}
+			Use::Result { node, .. } => node == id,
+		})
+	}
+
+	fn hoist<T>(&mut self, nodes: &mut DataFlowGraph<T>, id: Id)
+	where
+		T: Value + Arity + ParametersMut,
+	{
+		// Results still read inside of the loop become new loop variables.
+		for &hoisted in &self.order {
+			for link in nodes.outputs(hoisted) {
+				if self.is_used_inside(nodes, id, link) {
+					let node = nodes[id].as_theta().unwrap().argument;
+					let port = nodes.add_parameter(id, link);
+
+					nodes.replace_region_uses(&self.region_finder, link, Link { node, port });
+				}
+			}
+		}
+
+		let theta = nodes[id].as_theta().unwrap();
+		let argument = theta.argument;
+		let parameters = theta.parameters.clone();
+
+		for &hoisted in &self.order {
+			nodes.modify(hoisted, |node| {
+				for link in node.parameters_mut().filter(|link| link.node == argument) {
+					*link = parameters[usize::from(link.port)];
+				}
+			});
+		}
+	}
+
+	/// Moves the invariant nodes of the [`Theta`] node out of its region
+	/// and returns how many were moved.
+	///
+	/// [`Theta`]: crate::collection::node::Theta
+	pub fn run<T>(&mut self, nodes: &mut DataFlowGraph<T>, id: Id) -> usize
+	where
+		T: Value + Arity + ParametersMut,
+	{
+		if nodes.get(id).and_then(Node::as_theta).is_none() {
+			return 0;
+		}
+
+		let tracked = nodes.users().is_some();
+
+		if !tracked {
+			nodes.track_users();
+		}
+
+		self.find_invariants(nodes, id);
+		self.hoist(nodes, id);
+
+		if !tracked {
+			nodes.forget_users();
+		}
+
+		self.order.len()
+	}
+}
+
+impl Default for InvariantHoister {
+	#[inline]
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		collection::{data_flow_graph::DataFlowGraph, link::Link},
+		fixture::Named,
+		verify::verifier::Verifier,
+	};
+
+	use super::InvariantHoister;
+
+	#[test]
+	fn test_hoists_invariant_nodes() {
+		let mut nodes = DataFlowGraph::<Named>::new();
+
+		let counter = nodes.add_simple(Named("pure", Vec::new()));
+		let step = nodes.add_simple(Named("pure", Vec::new()));
+		let theta = nodes.theta(vec![counter, step], |theta| {
+			let counter = theta.input(0);
+			let step = theta.input(1);
+			let constant = theta.add_simple(Named("pure", Vec::new()));
+			let scaled = theta.add_simple(Named("pure", vec![step, constant]));
+			let printed = theta.add_simple(Named("print", vec![scaled]));
+			let next = theta.add_simple(Named("pure", vec![counter, printed]));
+
+			(vec![next, step], next)
+		});
+
+		assert_eq!(InvariantHoister::new().run(&mut nodes, theta.node), 2);
+
+		let theta = nodes[theta.node].as_theta().unwrap();
+		let scaled = theta.parameters[2];
+		let printed = nodes[theta.results[0].node].parameters().nth(1).unwrap();
+		let link = Link {
+			node: theta.argument,
+			port: 2,
+		};
+
+		assert!(theta.is_invariant(2));
+		assert_eq!(nodes[printed.node].parameters().next(), Some(&link));
+		assert_eq!(nodes[scaled.node].parameters().next(), Some(&step));
+		assert!(nodes.users().is_none());
+		assert!(Verifier::new().run(&nodes).is_empty());
+	}
+
+	#[test]
+	fn test_keeps_nodes_reading_loop_through_regions() {
+		let mut nodes = DataFlowGraph::<Named>::new();
+
+		let start = nodes.add_simple(Named("pure", Vec::new()));
+		let step = nodes.add_simple(Named("pure", Vec::new()));
+		let theta = nodes.theta(vec![start, step], |theta| {
+			let counter = theta.input(0);
+			let step = theta.input(1);
+
+			// The gamma only reads the loop from inside of its regions.
+			let gamma = theta.gamma(start, Vec::new(), 2, |gamma| {
+				vec![gamma.add_simple(Named("pure", vec![counter]))]
+			});
+			let next = theta.add_simple(Named("pure", vec![step, gamma]));
+
+			(vec![next, step], next)
+		});
+
+		assert_eq!(InvariantHoister::new().run(&mut nodes, theta.node), 0);
+		assert!(Verifier::new().run(&nodes).is_empty());
+	}
+}
@@ -0,0 +1,363 @@
+use std::collections::HashSet;
+
+use crate::collection::{
+	data_flow_graph::DataFlowGraph,
+	link::{Id, Link},
+	node::{Arity, Node, Parameters, ParametersMut},
+};
+
+/// A node that may call a function.
+pub trait Apply: Parameters {
+	/// Returns whether the node calls the function of its first parameter with the rest as inputs.
+	/// The results of such a node are the results of the function.
+	fn is_apply(&self) -> bool;
+}
+
+/// A function inliner.
+/// It replaces calls of known [`Lambda`] nodes with a copy of their region.
+///
+/// [`Lambda`]: crate::collection::node::Lambda
+pub struct LambdaInliner {
+	path: Vec<(Id, usize)>,
+	seen: HashSet<Link>,
+}
+
+impl LambdaInliner {
+	/// Creates a new, reusable [`LambdaInliner`] instance.
+	#[inline]
+	#[must_use]
+	pub fn new() -> Self {
+		Self {
+			path: Vec::new(),
+			seen: HashSet::new(),
+		}
+	}
+
+	pub(crate) fn find_source<T>(nodes: &DataFlowGraph<T>, link: Link) -> Option<(Link, bool)> {
+		let argument = nodes.get(link.node)?.as_argument()?;
+		let port = usize::from(link.port);
+
+		// Only values that stay the same for the whole region can be followed.
+		let source = match nodes.get(argument.parent)? {
+			Node::Gamma(gamma) if port + 1 < gamma.parameters.len() => {
+				(gamma.parameters[port], true)
+			}
+			Node::Theta(theta) if port < theta.parameters.len() && theta.results[port] == link => {
+				(theta.parameters[port], true)
+			}
+			Node::Phi(phi) if port < phi.parameters.len() => (phi.parameters[port], true),
+			Node::Phi(phi) => (*phi.results.get(port - phi.parameters.len())?, false),
+			Node::Lambda(lambda) if port < lambda.parameters.len() => {
+				(lambda.parameters[port], true)
+			}
+			Node::Delta(delta) if port < delta.parameters.len() => (delta.parameters[port], true),
+			_ => return None,
+		};
+
+		Some(source)
+	}
+
+	/// Resolves the [`Lambda`] node called through the link, following region arguments.
+	///
+	/// [`Lambda`]: crate::collection::node::Lambda
+	pub fn resolve<T>(&mut self, nodes: &DataFlowGraph<T>, mut link: Link) -> Option<Id> {
+		self.path.clear();
+		self.seen.clear();
+
+		while self.seen.insert(link) {
+			if nodes.get(link.node)?.as_lambda().is_some() {
+				return Some(link.node);
+			}
+
+			let (source, is_outside) = Self::find_source(nodes, link)?;
+
+			if is_outside {
+				let argument = nodes[link.node].as_argument()?;
+
+				self.path.push((argument.parent, argument.region));
+			}
+
+			link = source;
+		}
+
+		None
+	}
+
+	fn route<T>(&self, nodes: &mut DataFlowGraph<T>, mut link: Link) -> Link
+	where
+		T: Parameters + ParametersMut,
+	{
+		for &(parent, region) in self.path.iter().rev() {
+			let port = nodes.add_parameter(parent, link);
+			let node = nodes[parent].as_arguments().unwrap()[region];
+
+			link = Link { node, port };
+		}
+
+		link
+	}
+
+	/// Inlines the function called by the `apply` node, returning whether it was found.
+	///
+	/// The bound parameters of the function are passed down into the region of the
+	/// `apply` node as needed, and the function itself is left untouched. Calls whose
+	/// inputs or results do not match those of the function are not inlined.
+	pub fn run<T>(&mut self, nodes: &mut DataFlowGraph<T>, apply: Id) -> bool
+	where
+		T: Apply + Arity + Clone + ParametersMut,
+	{
+		let Some(simple) = nodes.get(apply).and_then(Node::as_simple) else {
+			return false;
+		};
+
+		if !simple.is_apply() {
+			return false;
+		}
+
+		let mut parameters = simple.parameters().copied();
+		let Some(callee) = parameters.next() else {
+			return false;
+		};
+
+		let inputs: Vec<_> = parameters.collect();
+		let arity = simple.arity();
+		let Some(function) = self.resolve(nodes, callee) else {
+			return false;
+		};
+
+		let lambda = nodes[function].as_lambda().unwrap();
+
+		// Mismatched calls would leave uses of the missing ports behind.
+		if inputs.len() != lambda.inputs || arity != lambda.results.len() {
+			return false;
+		}
+
+		let argument = lambda.argument;
+		let bound = lambda.parameters.clone();
+		let results = lambda.results.clone();
+		let map = nodes.clone_subgraph(function, 0);
+		let mut results: Vec<_> = results
+			.into_iter()
+			.map(|link| Link {
+				node: map.get(&link.node).copied().unwrap_or(link.node),
+				port: link.port,
+			})
+			.collect();
+
+		// Results may read the `Argument` node directly, as in an identity function.
+		if let Some(&copy) = map.get(&argument) {
+			let used: HashSet<_> = map
+				.values()
+				.flat_map(|&id| {
+					let node = &nodes[id];
+					let results = node.as_results().unwrap_or_default();
+
+					node.parameters().chain(results.iter().flatten())
+				})
+				.chain(&results)
+				.filter(|link| link.node == copy)
+				.map(|link| usize::from(link.port))
+				.collect();
+
+			let sources = bound.iter().chain(&inputs).enumerate();
+
+			for (port, &link) in sources.filter(|v| used.contains(&v.0)) {
+				let from = Link {
+					node: copy,
+					port: port.try_into().unwrap(),
+				};
+				let to = if port < bound.len() {
+					self.route(nodes, link)
+				} else {
+					link
+				};
+
+				nodes.replace_uses(from, to);
+
+				for result in results.iter_mut().filter(|result| **result == from) {
+					*result = to;
+				}
+			}
+
+			nodes.remove_node(copy);
+		}
+
+		for (port, to) in results.into_iter().enumerate() {
+			let from = Link {
+				node: apply,
+				port: port.try_into().unwrap(),
+			};
+
+			nodes.replace_uses(from, to);
+		}
+
+		nodes.remove_node(apply);
+
+		true
+	}
+}
+
+impl Default for LambdaInliner {
+	#[inline]
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		collection::{
+			data_flow_graph::DataFlowGraph,
+			link::Link,
+			node::{Arity, Parameters, ParametersMut},
+		},
+		verify::verifier::Verifier,
+	};
+
+	use super::{Apply, LambdaInliner};
+
+	#[derive(Clone)]
+	struct Simple {
+		is_apply: bool,
+		parameters: Vec<Link>,
+	}
+
+	impl Simple {
+		const fn new(is_apply: bool, parameters: Vec<Link>) -> Self {
+			Self {
+				is_apply,
+				parameters,
+			}
+		}
+	}
+
+	impl Parameters for Simple {
+		type Iter<'a> = std::slice::Iter<'a, Link>;
+
+		fn parameters(&self) -> Self::Iter<'_> {
+			self.parameters.iter()
+		}
+	}
+
+	impl ParametersMut for Simple {
+		type IterMut<'a> = std::slice::IterMut<'a, Link>;
+
+		fn parameters_mut(&mut self) -> Self::IterMut<'_> {
+			self.parameters.iter_mut()
+		}
+	}
+
+	impl Arity for Simple {
+		fn arity(&self) -> usize {
+			1
+		}
+	}
+
+	impl Apply for Simple {
+		fn is_apply(&self) -> bool {
+			self.is_apply
+		}
+	}
+
+	#[test]
+	fn test_inlines_through_gamma() {
+		let mut nodes = DataFlowGraph::<Simple>::new();
+
+		let bound = nodes.add_simple(Simple::new(false, Vec::new()));
+		let input = nodes.add_simple(Simple::new(false, Vec::new()));
+		let predicate = nodes.add_simple(Simple::new(false, Vec::new()));
+
+		let argument_0 = nodes.add_argument();
+		let body = nodes.add_simple(Simple::new(
+			false,
+			vec![
+				argument_0,
+				Link {
+					node: argument_0.node,
+					port: 1,
+				},
+			],
+		));
+		let lambda = nodes.add_lambda(vec![bound], 1, argument_0.node, vec![body]);
+
+		let argument_1 = nodes.add_argument();
+		let argument_2 = nodes.add_argument();
+		let apply = nodes.add_simple(Simple::new(
+			true,
+			vec![
+				argument_1,
+				Link {
+					node: argument_1.node,
+					port: 1,
+				},
+			],
+		));
+		let gamma = nodes.add_gamma(
+			vec![lambda, input, predicate],
+			[argument_1.node, argument_2.node].into_iter().collect(),
+			[vec![apply], vec![argument_2]].into_iter().collect(),
+		);
+
+		assert!(LambdaInliner::new().run(&mut nodes, apply.node));
+		assert!(nodes.get(apply.node).is_none());
+
+		let gamma = nodes[gamma.node].as_gamma().unwrap();
+		let result = gamma.results[0][0];
+
+		assert_ne!(result.node, body.node);
+		assert_eq!(gamma.parameters, [lambda, input, bound, predicate]);
+		assert_eq!(
+			nodes[result.node].parameters().copied().collect::<Vec<_>>(),
+			[
+				Link {
+					node: argument_1.node,
+					port: 2,
+				},
+				Link {
+					node: argument_1.node,
+					port: 1,
+				},
+			]
+		);
+	}
+
+	#[test]
+	fn test_inlines_identity() {
+		let mut nodes = DataFlowGraph::<Simple>::new();
+
+		let input = nodes.add_simple(Simple::new(false, Vec::new()));
+		let argument = nodes.add_argument();
+		let lambda = nodes.add_lambda(Vec::new(), 1, argument.node, vec![argument]);
+		let apply = nodes.add_simple(Simple::new(true, vec![lambda, input]));
+		let user = nodes.add_simple(Simple::new(false, vec![apply]));
+
+		assert!(LambdaInliner::new().run(&mut nodes, apply.node));
+		assert_eq!(nodes[user.node].parameters().next(), Some(&input));
+		assert_eq!(nodes.len(), 4);
+		assert!(Verifier::new().run(&nodes).is_empty());
+	}
+	#[test]
+	fn test_rejects_mismatched_calls() {
+		let mut nodes = DataFlowGraph::<Simple>::new();
+
+		let input = nodes.add_simple(Simple::new(false, Vec::new()));
+		let argument = nodes.add_argument();
+		let body = nodes.add_simple(Simple::new(
+			false,
+			vec![
+				argument,
+				Link {
+					node: argument.node,
+					port: 1,
+				},
+			],
+		));
+		let lambda = nodes.add_lambda(Vec::new(), 2, argument.node, vec![body]);
+		let apply = nodes.add_simple(Simple::new(true, vec![lambda, input]));
+
+		assert!(!LambdaInliner::new().run(&mut nodes, apply.node));
+		assert_eq!(nodes.len(), 5);
+		assert!(Verifier::new().run(&nodes).is_empty());
+	}
+}
@@ -0,0 +1,210 @@
+use crate::collection::{
+	data_flow_graph::DataFlowGraph,
+	link::{Id, Link},
+	node::{Node, Parameters, ParametersMut},
+};
+
+fn find_ports(node: Id, count: usize) -> impl Iterator<Item = Link> {
+	(0..count).map(move |port| Link {
+		node,
+		port: port.try_into().unwrap(),
+	})
+}
+
+/// A [`Theta`] node unroller.
+/// It can peel the first iteration off of a loop, or repeat its region
+/// a number of times per iteration.
+///
+/// Predicates select the first region of a [`Gamma`] node when they are false,
+/// and the second one when they are true.
+///
+/// [`Theta`]: crate::collection::node::Theta
+/// [`Gamma`]: crate::collection::node::Gamma
+pub struct LoopUnroller {
+	results: Vec<Link>,
+}
+
+impl LoopUnroller {
+	/// Creates a new, reusable [`LoopUnroller`] instance.
+	#[inline]
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			results: Vec::new(),
+		}
+	}
+
+	// Adds a `Gamma` node that either passes the values through or gives them
+	// to another copy of the region, returning the values and predicate after it.
+	fn add_guard<T>(&mut self, nodes: &mut DataFlowGraph<T>, id: Id, predicate: Link)
+	where
+		T: Clone + Parameters + ParametersMut,
+	{
+		let count = self.results.len();
+		let skipped = nodes.add_argument().node;
+		let repeated = nodes.add_argument().node;
+
+		let inputs: Vec<_> = find_ports(repeated, count).collect();
+		let results = [
+			find_ports(skipped, count + 1).collect(),
+			nodes.clone_region(id, 0, &inputs),
+		];
+
+		// The predicate is passed in too, as it is false in the first region.
+		self.results.push(predicate);
+		self.results.push(predicate);
+
+		let gamma = nodes.add_gamma(
+			std::mem::take(&mut self.results),
+			[skipped, repeated].into_iter().collect(),
+			results.into_iter().collect(),
+		);
+
+		self.results.extend(find_ports(gamma.node, count + 1));
+	}
+
+	/// Peels the first iteration off of the [`Theta`] node, placing the node in
+	/// a [`Gamma`] node that only runs it if the first iteration asks to repeat.
+	/// Returns the [`Link`] of the [`Gamma`] node, whose outputs replace those of the loop.
+	///
+	/// [`Theta`]: crate::collection::node::Theta
+	/// [`Gamma`]: crate::collection::node::Gamma
+	pub fn peel<T>(&mut self, nodes: &mut DataFlowGraph<T>, id: Id) -> Option<Link>
+	where
+		T: Clone + Parameters + ParametersMut,
+	{
+		let parameters = nodes.get(id).and_then(Node::as_theta)?.parameters.clone();
+		let count = parameters.len();
+
+		let mut results = nodes.clone_region(id, 0, &parameters);
+		let predicate = results.pop().unwrap();
+
+		let skipped = nodes.add_argument().node;
+		let repeated = nodes.add_argument().node;
+
+		results.push(predicate);
+
+		let gamma = nodes.add_gamma(
+			results,
+			[skipped, repeated].into_iter().collect(),
+			[
+				find_ports(skipped, count).collect(),
+				vec![Link::dangling(); count],
+			]
+			.into_iter()
+			.collect(),
+		);
+
+		for (from, to) in find_ports(id, count).zip(find_ports(gamma.node, count)) {
+			nodes.replace_uses(from, to);
+		}
+
+		for (index, link) in find_ports(id, count).enumerate() {
+			nodes.set_result(gamma.node, 1, index, link);
+		}
+
+		nodes.modify(id, |node| {
+			node.as_mut_theta().unwrap().parameters = find_ports(repeated, count).collect();
+		});
+
+		Some(gamma)
+	}
+
+	/// Repeats the region of the [`Theta`] node `factor` times per iteration,
+	/// checking the predicate between every copy. Returns whether the node was found.
+	///
+	/// [`Theta`]: crate::collection::node::Theta
+	pub fn unroll<T>(&mut self, nodes: &mut DataFlowGraph<T>, id: Id, factor: usize) -> bool
+	where
+		T: Clone + Parameters + ParametersMut,
+	{
+		let Some(theta) = nodes.get(id).and_then(Node::as_theta) else {
+			return false;
+		};
+
+		self.results.clear();
+		self.results.extend_from_slice(&theta.results);
+
+		for _ in 1..factor {
+			let predicate = self.results.pop().unwrap();
+
+			self.add_guard(nodes, id, predicate);
+		}
+
+		for (index, &link) in self.results.iter().enumerate() {
+			nodes.set_result(id, 0, index, link);
+		}
+
+		true
+	}
+}
+
+impl Default for LoopUnroller {
+	#[inline]
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		collection::{data_flow_graph::DataFlowGraph, link::Link},
+		fixture::Simple,
+		verify::verifier::Verifier,
+	};
+
+	use super::LoopUnroller;
+
+	fn add_loop(nodes: &mut DataFlowGraph<Simple>) -> (Link, Link) {
+		let counter = nodes.add_simple(Simple(Vec::new()));
+		let theta = nodes.theta(vec![counter], |theta| {
+			let counter = theta.input(0);
+			let next = theta.add_simple(Simple(vec![counter]));
+			let predicate = theta.add_simple(Simple(vec![next]));
+
+			(vec![next], predicate)
+		});
+
+		(theta, nodes.add_simple(Simple(vec![theta])))
+	}
+
+	#[test]
+	fn test_peels_first_iteration() {
+		let mut nodes = DataFlowGraph::new();
+		let (theta, user) = add_loop(&mut nodes);
+
+		let gamma = LoopUnroller::new().peel(&mut nodes, theta.node).unwrap();
+		let parameters = &nodes[gamma.node].as_gamma().unwrap().parameters;
+
+		assert_eq!(nodes[user.node].as_simple().unwrap().0, [gamma]);
+		assert_eq!(
+			nodes[parameters[1].node].as_simple().unwrap().0,
+			[parameters[0]]
+		);
+		assert_eq!(nodes[gamma.node].as_gamma().unwrap().results[1], [theta]);
+		assert!(Verifier::new().run(&nodes).is_empty());
+	}
+
+	#[test]
+	fn test_unrolls_region() {
+		let mut nodes = DataFlowGraph::new();
+		let (theta, user) = add_loop(&mut nodes);
+
+		assert!(LoopUnroller::new().unroll(&mut nodes, theta.node, 3));
+
+		let results = &nodes[theta.node].as_theta().unwrap().results;
+		let outer = nodes[results[0].node].as_gamma().unwrap();
+		let inner = outer.parameters[0].node;
+		let predicate = Link {
+			node: inner,
+			port: 1,
+		};
+
+		assert_eq!(results[1].node, results[0].node);
+		assert_eq!(outer.parameters[1..], [predicate, predicate]);
+		assert_eq!(nodes[inner].as_gamma().unwrap().results.len(), 2);
+		assert_eq!(nodes[user.node].as_simple().unwrap().0, [theta]);
+		assert!(Verifier::new().run(&nodes).is_empty());
+	}
+}
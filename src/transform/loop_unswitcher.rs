@@ -0,0 +1,210 @@
+use crate::{
+	collection::{
+		data_flow_graph::DataFlowGraph,
+		link::{Id, Link},
+		node::{Node, Parameters, ParametersMut},
+	},
+	visit::region_finder::RegionFinder,
+};
+
+use super::gamma_simplifier::GammaSimplifier;
+
+/// A loop unswitcher.
+/// It moves a [`Gamma`] node with a loop-invariant predicate out of a [`Theta`] node,
+/// giving every region of the [`Gamma`] node its own copy of the loop.
+///
+/// [`Gamma`]: crate::collection::node::Gamma
+/// [`Theta`]: crate::collection::node::Theta
+pub struct LoopUnswitcher {
+	region_finder: RegionFinder,
+}
+
+impl LoopUnswitcher {
+	/// Creates a new, reusable [`LoopUnswitcher`] instance.
+	#[inline]
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			region_finder: RegionFinder::new(),
+		}
+	}
+
+	fn find_invariant_port<T>(nodes: &DataFlowGraph<T>, id: Id, gamma: Id) -> Option<usize> {
+		let theta = nodes[id].as_theta().unwrap();
+		let predicate = *nodes[gamma].as_gamma()?.parameters.last()?;
+		let port = usize::from(predicate.port);
+
+		(predicate.node == theta.argument && theta.is_invariant(port)).then_some(port)
+	}
+
+	fn find_gamma<T: Parameters>(
+		&mut self,
+		nodes: &DataFlowGraph<T>,
+		id: Id,
+	) -> Option<(Id, usize)> {
+		self.region_finder.run(nodes, id, 0);
+		self.region_finder.nodes().iter().find_map(|&gamma| {
+			Self::find_invariant_port(nodes, id, gamma).map(|port| (gamma, port))
+		})
+	}
+
+	// Copies the loop into the region with the `Gamma` node replaced by one of its regions.
+	fn add_specialized<T>(
+		nodes: &mut DataFlowGraph<T>,
+		id: Id,
+		gamma: Id,
+		region: usize,
+		argument: Id,
+	) -> Link
+	where
+		T: Clone + Parameters + ParametersMut,
+	{
+		let theta = nodes[id].as_theta().unwrap();
+		let inputs = theta.parameters.len();
+		let original = theta.argument;
+		let results = theta.results.clone();
+		let map = nodes.clone_subgraph(id, 0);
+
+		let parameters = (0..inputs)
+			.map(|port| Link {
+				node: argument,
+				port: port.try_into().unwrap(),
+			})
+			.collect();
+		let inner = map
+			.get(&original)
+			.copied()
+			.unwrap_or_else(|| nodes.add_argument().node);
+		let results = results
+			.into_iter()
+			.map(|link| Link {
+				node: map.get(&link.node).copied().unwrap_or(link.node),
+				port: link.port,
+			})
+			.collect();
+
+		let copy = nodes.add_theta(parameters, inner, results);
+
+		GammaSimplifier::inline_region(nodes, map[&gamma], region);
+
+		copy
+	}
+
+	/// Unswitches the first [`Gamma`] node of the [`Theta`] node's region whose
+	/// predicate is a loop-invariant variable. Returns the [`Link`] of the new
+	/// [`Gamma`] node, whose outputs replace those of the loop.
+	///
+	/// The original loop is removed along with the nodes of its region.
+	///
+	/// [`Gamma`]: crate::collection::node::Gamma
+	/// [`Theta`]: crate::collection::node::Theta
+	pub fn run<T>(&mut self, nodes: &mut DataFlowGraph<T>, id: Id) -> Option<Link>
+	where
+		T: Clone + Parameters + ParametersMut,
+	{
+		nodes.get(id).and_then(Node::as_theta)?;
+
+		let (gamma, port) = self.find_gamma(nodes, id)?;
+		let regions = nodes[gamma].as_gamma().unwrap().results.len();
+
+		let mut parameters = nodes[id].as_theta().unwrap().parameters.clone();
+		let outputs = parameters.len();
+		let mut arguments = Vec::with_capacity(regions);
+		let mut results = Vec::with_capacity(regions);
+
+		for region in 0..regions {
+			let argument = nodes.add_argument().node;
+			let copy = Self::add_specialized(nodes, id, gamma, region, argument);
+			let outputs = (0..outputs).map(|port| Link {
+				node: copy.node,
+				port: port.try_into().unwrap(),
+			});
+
+			arguments.push(argument);
+			results.push(outputs.collect());
+		}
+
+		parameters.push(parameters[port]);
+
+		let unswitched = nodes.add_gamma(
+			parameters,
+			arguments.into_iter().collect(),
+			results.into_iter().collect(),
+		);
+
+		for port in 0..outputs {
+			let port = port.try_into().unwrap();
+			let from = Link { node: id, port };
+			let to = Link {
+				node: unswitched.node,
+				port,
+			};
+
+			nodes.replace_uses(from, to);
+		}
+
+		nodes.remove_subgraph(id, 0);
+		nodes.remove_node(id);
+
+		Some(unswitched)
+	}
+}
+
+impl Default for LoopUnswitcher {
+	#[inline]
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		collection::data_flow_graph::DataFlowGraph, fixture::Simple, verify::verifier::Verifier,
+	};
+
+	use super::LoopUnswitcher;
+
+	#[test]
+	fn test_unswitches_invariant_gamma() {
+		let mut nodes = DataFlowGraph::<Simple>::new();
+
+		let counter = nodes.add_simple(Simple(Vec::new()));
+		let flag = nodes.add_simple(Simple(Vec::new()));
+		let theta = nodes.theta(vec![counter, flag], |theta| {
+			let counter = theta.input(0);
+			let flag = theta.input(1);
+			let next = theta.gamma(flag, vec![counter], 2, |gamma| {
+				let input = gamma.input(0);
+
+				if gamma.index() == 0 {
+					vec![gamma.add_simple(Simple(vec![input]))]
+				} else {
+					vec![input]
+				}
+			});
+			let predicate = theta.add_simple(Simple(vec![next]));
+
+			(vec![next, flag], predicate)
+		});
+		let user = nodes.add_simple(Simple(vec![theta]));
+
+		let gamma = LoopUnswitcher::new().run(&mut nodes, theta.node).unwrap();
+
+		let outer = nodes[gamma.node].as_gamma().unwrap();
+
+		assert!(nodes.get(theta.node).is_none());
+		assert_eq!(nodes[user.node].as_simple().unwrap().0, [gamma]);
+		assert_eq!(outer.parameters, [counter, flag, flag]);
+
+		for (region, results) in outer.results.iter().enumerate() {
+			let copy = nodes[results[0].node].as_theta().unwrap();
+			let next = copy.results[0].node;
+
+			assert_eq!(nodes[next].as_simple().is_some(), region == 0);
+			assert_eq!(nodes[next].as_argument().is_some(), region == 1);
+		}
+
+		assert!(Verifier::new().run(&nodes).is_empty());
+	}
+}
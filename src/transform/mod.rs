@@ -0,0 +1,11 @@
+pub mod constant_folder;
+pub mod gamma_fuser;
+pub mod gamma_simplifier;
+pub mod gamma_sinker;
+pub mod invariant_hoister;
+pub mod lambda_inliner;
+pub mod loop_unroller;
+pub mod loop_unswitcher;
+pub mod port_pruner;
+pub mod theta_fuser;
+pub mod value_numberer;
@@ -0,0 +1,318 @@
+use crate::collection::{
+	data_flow_graph::DataFlowGraph,
+	link::{Id, Link},
+	node::{Node, Parameters, ParametersMut},
+	users::Use,
+};
+
+fn retain_indexed<U>(list: &mut Vec<U>, keep: &[bool]) {
+	let mut keep = keep.iter();
+
+	list.retain(|_| keep.next().copied().unwrap_or(true));
+}
+
+fn remap_ports<T>(nodes: &mut DataFlowGraph<T>, node: Id, keep: &[bool])
+where
+	T: Parameters + ParametersMut,
+{
+	let kept = keep.iter().enumerate().filter(|v| *v.1).map(|v| v.0);
+
+	for (next, port) in kept.enumerate() {
+		if port != next {
+			let from = Link {
+				node,
+				port: port.try_into().unwrap(),
+			};
+			let to = Link {
+				node,
+				port: next.try_into().unwrap(),
+			};
+
+			nodes.replace_uses(from, to);
+		}
+	}
+}
+
+fn is_used<T: Parameters>(nodes: &DataFlowGraph<T>, node: Id, port: usize) -> bool {
+	let link = Link {
+		node,
+		port: port.try_into().unwrap(),
+	};
+
+	!nodes.users_of(link).is_empty()
+}
+
+/// A pruner of unused compound node ports.
+/// It removes outputs nobody reads along with their region results,
+/// and parameters whose [`Argument`] port nobody reads.
+///
+/// [`Argument`]: crate::collection::node::Argument
+pub struct PortPruner {
+	compounds: Vec<Id>,
+}
+
+impl PortPruner {
+	/// Creates a new, reusable [`PortPruner`] instance.
+	#[inline]
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			compounds: Vec::new(),
+		}
+	}
+
+	pub(crate) fn prune_gamma<T>(nodes: &mut DataFlowGraph<T>, id: Id, is_root: bool) -> bool
+	where
+		T: Parameters + ParametersMut,
+	{
+		let gamma = nodes[id].as_gamma().unwrap();
+		let arguments = gamma.arguments.to_vec();
+		let outputs = gamma.results.first().map_or(0, Vec::len);
+		let inputs = gamma.parameters.len().saturating_sub(1);
+
+		let keep_outputs: Vec<_> = (0..outputs)
+			.map(|port| is_root || is_used(nodes, id, port))
+			.collect();
+		let keep_inputs: Vec<_> = (0..inputs)
+			.map(|port| arguments.iter().any(|&node| is_used(nodes, node, port)))
+			.collect();
+
+		if keep_outputs.iter().chain(&keep_inputs).all(|&keep| keep) {
+			return false;
+		}
+
+		nodes.modify(id, |node| {
+			let gamma = node.as_mut_gamma().unwrap();
+
+			for results in gamma.results.iter_mut() {
+				retain_indexed(results, &keep_outputs);
+			}
+
+			retain_indexed(&mut gamma.parameters, &keep_inputs);
+		});
+
+		remap_ports(nodes, id, &keep_outputs);
+
+		for argument in arguments {
+			remap_ports(nodes, argument, &keep_inputs);
+		}
+
+		true
+	}
+
+	fn prune_theta<T>(nodes: &mut DataFlowGraph<T>, id: Id, is_root: bool) -> bool
+	where
+		T: Parameters + ParametersMut,
+	{
+		let theta = nodes[id].as_theta().unwrap();
+		let argument = theta.argument;
+
+		// A loop variable only passed back into itself is as good as unused.
+		let keep: Vec<_> = (0..theta.parameters.len())
+			.map(|port| {
+				let loop_back = Use::Result {
+					node: id,
+					region: 0,
+					index: port,
+				};
+				let link = Link {
+					node: argument,
+					port: port.try_into().unwrap(),
+				};
+
+				is_root
+					|| is_used(nodes, id, port)
+					|| nodes.users_of(link).iter().any(|&user| user != loop_back)
+			})
+			.collect();
+
+		if keep.iter().all(|&keep| keep) {
+			return false;
+		}
+
+		nodes.modify(id, |node| {
+			let theta = node.as_mut_theta().unwrap();
+
+			retain_indexed(&mut theta.parameters, &keep);
+			retain_indexed(&mut theta.results, &keep);
+		});
+
+		remap_ports(nodes, id, &keep);
+		remap_ports(nodes, argument, &keep);
+
+		true
+	}
+
+	// Functions and globals keep their only output, so only their context is pruned.
+	fn prune_context<T>(nodes: &mut DataFlowGraph<T>, id: Id) -> bool
+	where
+		T: Parameters + ParametersMut,
+	{
+		let node = &nodes[id];
+		let argument = node.as_arguments().unwrap()[0];
+		let arity = node.argument_arity().unwrap();
+
+		let keep_parameters: Vec<_> = (0..node.as_parameters().unwrap().len())
+			.map(|port| is_used(nodes, argument, port))
+			.collect();
+
+		if keep_parameters.iter().all(|&keep| keep) {
+			return false;
+		}
+
+		let mut keep_arguments = keep_parameters.clone();
+
+		keep_arguments.resize(arity, true);
+
+		nodes.modify(id, |node| {
+			retain_indexed(node.as_mut_parameters().unwrap(), &keep_parameters);
+		});
+
+		remap_ports(nodes, argument, &keep_arguments);
+
+		true
+	}
+
+	fn prune_phi<T>(nodes: &mut DataFlowGraph<T>, id: Id, is_root: bool) -> bool
+	where
+		T: Parameters + ParametersMut,
+	{
+		let phi = nodes[id].as_phi().unwrap();
+		let argument = phi.argument;
+		let parameters = phi.parameters.len();
+
+		let keep_parameters: Vec<_> = (0..parameters)
+			.map(|port| is_used(nodes, argument, port))
+			.collect();
+		let keep_results: Vec<_> = (0..phi.results.len())
+			.map(|port| {
+				is_root || is_used(nodes, id, port) || is_used(nodes, argument, parameters + port)
+			})
+			.collect();
+
+		if keep_parameters
+			.iter()
+			.chain(&keep_results)
+			.all(|&keep| keep)
+		{
+			return false;
+		}
+
+		let keep_arguments: Vec<_> = keep_parameters
+			.iter()
+			.chain(&keep_results)
+			.copied()
+			.collect();
+
+		nodes.modify(id, |node| {
+			let phi = node.as_mut_phi().unwrap();
+
+			retain_indexed(&mut phi.parameters, &keep_parameters);
+			retain_indexed(&mut phi.results, &keep_results);
+		});
+
+		remap_ports(nodes, id, &keep_results);
+		remap_ports(nodes, argument, &keep_arguments);
+
+		true
+	}
+
+	pub(crate) fn prune<T>(nodes: &mut DataFlowGraph<T>, id: Id, is_root: bool) -> bool
+	where
+		T: Parameters + ParametersMut,
+	{
+		match nodes[id] {
+			Node::Simple(_) | Node::Argument(_) | Node::Omega(_) => false,
+			Node::Gamma(_) => Self::prune_gamma(nodes, id, is_root),
+			Node::Theta(_) => Self::prune_theta(nodes, id, is_root),
+			Node::Phi(_) => Self::prune_phi(nodes, id, is_root),
+			Node::Lambda(_) | Node::Delta(_) => Self::prune_context(nodes, id),
+		}
+	}
+
+	/// Prunes unused ports of every compound node, collecting garbage between rounds,
+	/// until no more ports can be removed. The outputs of the roots are always kept.
+	pub fn run<T>(&mut self, nodes: &mut DataFlowGraph<T>, roots: &[Id])
+	where
+		T: Parameters + ParametersMut,
+	{
+		let tracked = nodes.users().is_some();
+
+		if !tracked {
+			nodes.track_users();
+		}
+
+		loop {
+			nodes.collect_garbage(roots.iter().copied());
+
+			self.compounds.clear();
+			self.compounds.extend(
+				nodes
+					.iter()
+					.filter(|(_, node)| node.as_results().is_some())
+					.map(|(id, _)| id),
+			);
+
+			let mut changed = false;
+
+			for &id in &self.compounds {
+				changed |= Self::prune(nodes, id, roots.contains(&id));
+			}
+
+			if !changed {
+				break;
+			}
+		}
+
+		if !tracked {
+			nodes.forget_users();
+		}
+	}
+}
+
+impl Default for PortPruner {
+	#[inline]
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		collection::{data_flow_graph::DataFlowGraph, link::Link},
+		fixture::Simple,
+	};
+
+	use super::PortPruner;
+
+	#[test]
+	fn test_prunes_loop_variable() {
+		let mut nodes = DataFlowGraph::<Simple>::new();
+
+		let node_0 = nodes.add_simple(Simple(Vec::new()));
+		let node_1 = nodes.add_simple(Simple(Vec::new()));
+		let argument = nodes.add_argument();
+		let node_2 = nodes.add_simple(Simple(vec![Link {
+			node: argument.node,
+			port: 1,
+		}]));
+		let node_3 = nodes.add_simple(Simple(Vec::new()));
+		let node_4 = nodes.add_theta(
+			vec![node_0, node_1],
+			argument.node,
+			vec![argument, node_2, node_3],
+		);
+		let node_5 = nodes.add_simple(Simple(vec![node_4]));
+
+		PortPruner::new().run(&mut nodes, &[node_5.node]);
+
+		let theta = nodes[node_4.node].as_theta().unwrap();
+
+		assert_eq!(theta.parameters, [node_0]);
+		assert_eq!(theta.results, [argument, node_3]);
+		assert!(nodes.get(node_1.node).is_none());
+		assert!(nodes.get(node_2.node).is_none());
+		assert!(nodes.users().is_none());
+	}
+}
@@ -0,0 +1,348 @@
+use std::collections::HashMap;
+
+use crate::{
+	collection::{
+		data_flow_graph::DataFlowGraph,
+		link::{Id, Link},
+		node::{Node, Parameters, ParametersMut},
+	},
+	visit::dependency_finder::DependencyFinder,
+};
+
+use super::value_numberer::Value;
+
+/// A [`Theta`] node fuser.
+/// It merges two [`Theta`] nodes into one when neither depends on the other and
+/// their predicates are equal in every iteration.
+///
+/// Predicates are equal when they are computed by pairs of equal nodes from pairs of
+/// loop variables that start with the same [`Link`] and are updated by pairs of equal nodes.
+///
+/// [`Theta`]: crate::collection::node::Theta
+pub struct ThetaFuser {
+	dependency_finder: DependencyFinder,
+
+	pairs: HashMap<Id, Id>,
+	ports: HashMap<u16, u16>,
+	stack: Vec<(Link, Link)>,
+}
+
+impl ThetaFuser {
+	/// Creates a new, reusable [`ThetaFuser`] instance.
+	#[inline]
+	#[must_use]
+	pub fn new() -> Self {
+		Self {
+			dependency_finder: DependencyFinder::new(),
+
+			pairs: HashMap::new(),
+			ports: HashMap::new(),
+			stack: Vec::new(),
+		}
+	}
+
+	// Loop variables are assumed equal while checking, so that their updates
+	// may refer back to them.
+	fn is_variable_equal<T>(
+		&mut self,
+		nodes: &DataFlowGraph<T>,
+		first: Id,
+		second: Id,
+		lhs: u16,
+		rhs: u16,
+	) -> bool {
+		if let Some(&other) = self.ports.get(&lhs) {
+			return other == rhs;
+		}
+
+		let lhs_theta = nodes[first].as_theta().unwrap();
+		let rhs_theta = nodes[second].as_theta().unwrap();
+
+		self.ports.insert(lhs, rhs);
+
+		let (lhs, rhs) = (usize::from(lhs), usize::from(rhs));
+		let (Some(lhs_link), Some(rhs_link)) =
+			(lhs_theta.parameters.get(lhs), rhs_theta.parameters.get(rhs))
+		else {
+			return false;
+		};
+
+		self.stack
+			.push((lhs_theta.results[lhs], rhs_theta.results[rhs]));
+
+		lhs_link == rhs_link
+	}
+
+	fn is_pair_equal<T: Value>(
+		&mut self,
+		nodes: &DataFlowGraph<T>,
+		(first, second): (Id, Id),
+		lhs: Link,
+		rhs: Link,
+	) -> bool {
+		// Links from outside of both loops are the same in every iteration.
+		if lhs == rhs {
+			return true;
+		}
+
+		let lhs_argument = nodes[first].as_theta().unwrap().argument;
+		let rhs_argument = nodes[second].as_theta().unwrap().argument;
+
+		if lhs.node == lhs_argument || rhs.node == rhs_argument {
+			return lhs.node == lhs_argument
+				&& rhs.node == rhs_argument
+				&& self.is_variable_equal(nodes, first, second, lhs.port, rhs.port);
+		}
+
+		if lhs.port != rhs.port {
+			return false;
+		}
+
+		if let Some(&other) = self.pairs.get(&lhs.node) {
+			return other == rhs.node;
+		}
+
+		let (Some(Node::Simple(left)), Some(Node::Simple(right))) =
+			(nodes.get(lhs.node), nodes.get(rhs.node))
+		else {
+			return false;
+		};
+
+		let (Some(left_key), Some(right_key)) = (left.key(), right.key()) else {
+			return false;
+		};
+
+		if left_key != right_key || left.parameters().count() != right.parameters().count() {
+			return false;
+		}
+
+		self.pairs.insert(lhs.node, rhs.node);
+		self.stack
+			.extend(left.parameters().copied().zip(right.parameters().copied()));
+
+		true
+	}
+
+	fn is_predicate_equal<T: Value>(
+		&mut self,
+		nodes: &DataFlowGraph<T>,
+		first: Id,
+		second: Id,
+	) -> bool {
+		let lhs = nodes[first].as_theta().unwrap().results.last().copied();
+		let rhs = nodes[second].as_theta().unwrap().results.last().copied();
+		let (Some(lhs), Some(rhs)) = (lhs, rhs) else {
+			return false;
+		};
+
+		self.pairs.clear();
+		self.ports.clear();
+		self.stack.clear();
+		self.stack.push((lhs, rhs));
+
+		while let Some((lhs, rhs)) = self.stack.pop() {
+			if !self.is_pair_equal(nodes, (first, second), lhs, rhs) {
+				return false;
+			}
+		}
+
+		true
+	}
+
+	/// Returns whether the two [`Theta`] nodes can be fused.
+	///
+	/// [`Theta`]: crate::collection::node::Theta
+	pub fn can_fuse<T: Value>(&mut self, nodes: &DataFlowGraph<T>, first: Id, second: Id) -> bool {
+		let is_theta = |id| nodes.get(id).and_then(Node::as_theta).is_some();
+
+		first != second
+			&& is_theta(first)
+			&& is_theta(second)
+			&& !self.dependency_finder.run(nodes, first, second)
+			&& !self.dependency_finder.run(nodes, second, first)
+			&& self.is_predicate_equal(nodes, first, second)
+	}
+
+	fn fuse<T>(nodes: &mut DataFlowGraph<T>, first: Id, second: Id)
+	where
+		T: Parameters + ParametersMut,
+	{
+		let theta = nodes[second].as_theta().unwrap();
+		let argument = theta.argument;
+		let parameters = theta.parameters.clone();
+		let target = nodes[first].as_theta().unwrap().argument;
+
+		let ports: Vec<_> = parameters
+			.into_iter()
+			.enumerate()
+			.map(|(index, link)| {
+				let port = nodes.add_parameter(first, link);
+				let from = Link {
+					node: argument,
+					port: index.try_into().unwrap(),
+				};
+				let to = Link { node: target, port };
+
+				nodes.replace_uses(from, to);
+
+				port
+			})
+			.collect();
+
+		let results = nodes[second].as_theta().unwrap().results.clone();
+
+		for (index, &port) in ports.iter().enumerate() {
+			let from = Link {
+				node: second,
+				port: index.try_into().unwrap(),
+			};
+			let to = Link { node: first, port };
+
+			nodes.set_result(first, 0, port.into(), results[index]);
+			nodes.replace_uses(from, to);
+		}
+
+		nodes.remove_node(second);
+		nodes.remove_node(argument);
+	}
+
+	/// Fuses the `second` [`Theta`] node into the `first` one if they can be fused,
+	/// returning whether they were. Loop variables of the `second` node are placed
+	/// after those of the `first` one, and its predicate is left for garbage collection.
+	///
+	/// [`Theta`]: crate::collection::node::Theta
+	pub fn run<T>(&mut self, nodes: &mut DataFlowGraph<T>, first: Id, second: Id) -> bool
+	where
+		T: Value + ParametersMut,
+	{
+		if !self.can_fuse(nodes, first, second) {
+			return false;
+		}
+
+		Self::fuse(nodes, first, second);
+
+		true
+	}
+}
+
+impl Default for ThetaFuser {
+	#[inline]
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		collection::{data_flow_graph::DataFlowGraph, link::Link},
+		fixture::Named,
+		verify::verifier::Verifier,
+	};
+
+	use super::ThetaFuser;
+
+	fn add_loop(nodes: &mut DataFlowGraph<Named>, start: Link, name: &'static str) -> Link {
+		let input = nodes.add_simple(Named("input", Vec::new()));
+
+		nodes.theta(vec![start, input], |theta| {
+			let counter = theta.input(0);
+			let value = theta.input(1);
+			let next = theta.add_simple(Named("increment", vec![counter]));
+			let predicate = theta.add_simple(Named("less", vec![next]));
+			let value = theta.add_simple(Named(name, vec![value]));
+
+			(vec![next, value], predicate)
+		})
+	}
+
+	#[test]
+	fn test_fuses_equal_loops() {
+		let mut nodes = DataFlowGraph::<Named>::new();
+
+		let zero = nodes.add_simple(Named("zero", Vec::new()));
+		let one = nodes.add_simple(Named("one", Vec::new()));
+		let theta_0 = add_loop(&mut nodes, zero, "add");
+		let theta_1 = add_loop(&mut nodes, zero, "multiply");
+		let theta_2 = add_loop(&mut nodes, one, "add");
+		let user = nodes.add_simple(Named(
+			"user",
+			vec![
+				Link {
+					node: theta_1.node,
+					port: 1,
+				},
+				theta_2,
+			],
+		));
+
+		let mut fuser = ThetaFuser::new();
+
+		assert!(!fuser.can_fuse(&nodes, theta_0.node, theta_2.node));
+		assert!(fuser.run(&mut nodes, theta_0.node, theta_1.node));
+		assert!(nodes.get(theta_1.node).is_none());
+
+		let theta = nodes[theta_0.node].as_theta().unwrap();
+		let fused = Link {
+			node: theta_0.node,
+			port: 3,
+		};
+
+		assert_eq!(theta.parameters.len(), 4);
+		assert_eq!(theta.results.len(), 5);
+		assert_eq!(nodes[user.node].parameters().next(), Some(&fused));
+		assert!(Verifier::new().run(&nodes).is_empty());
+	}
+
+	#[test]
+	fn test_keeps_loops_used_by_regions() {
+		let mut nodes = DataFlowGraph::<Named>::new();
+
+		let zero = nodes.add_simple(Named("zero", Vec::new()));
+		let theta_0 = add_loop(&mut nodes, zero, "add");
+		let theta_1 = nodes.theta(vec![zero, zero], |theta| {
+			let counter = theta.input(0);
+			let value = theta.input(1);
+			let next = theta.add_simple(Named("increment", vec![counter]));
+			let predicate = theta.add_simple(Named("less", vec![next]));
+			let value = theta.add_simple(Named("add", vec![value, theta_0]));
+
+			(vec![next, value], predicate)
+		});
+
+		assert!(!ThetaFuser::new().can_fuse(&nodes, theta_0.node, theta_1.node));
+	}
+
+	fn add_bounded_loop(nodes: &mut DataFlowGraph<Named>, starts: [Link; 2], limit: Link) -> Link {
+		nodes.theta(starts.to_vec(), |theta| {
+			let counter = theta.input(0);
+			let value = theta.input(1);
+			let next = theta.add_simple(Named("increment", vec![counter]));
+			let predicate = theta.add_simple(Named("less", vec![next, limit]));
+			let value = theta.add_simple(Named("add", vec![value]));
+
+			(vec![next, value], predicate)
+		})
+	}
+
+	#[test]
+	fn test_fuses_loops_sharing_outer_links() {
+		let mut nodes = DataFlowGraph::<Named>::new();
+
+		let limit = nodes.add_argument();
+		let zero = nodes.add_simple(Named("zero", Vec::new()));
+		let start = nodes.add_simple(Named("start", Vec::new()));
+		let theta_0 = add_bounded_loop(&mut nodes, [zero, start], limit);
+		let theta_1 = add_bounded_loop(&mut nodes, [zero, start], limit);
+		let output = Link {
+			node: theta_0.node,
+			port: 1,
+		};
+		let theta_2 = add_bounded_loop(&mut nodes, [zero, output], limit);
+
+		let mut fuser = ThetaFuser::new();
+
+		assert!(!fuser.can_fuse(&nodes, theta_0.node, theta_2.node));
+		assert!(fuser.can_fuse(&nodes, theta_0.node, theta_1.node));
+	}
+}
@@ -0,0 +1,412 @@
+use std::{collections::HashMap, hash::Hash};
+
+use crate::{
+	collection::{
+		data_flow_graph::DataFlowGraph,
+		link::{Id, Link},
+		node::{Arity, Node, Parameters, ParametersMut},
+	},
+	visit::region_finder::RegionFinder,
+};
+
+use super::{lambda_inliner::LambdaInliner, port_pruner::PortPruner};
+
+/// A node that may be equal to other nodes with the same parameters.
+pub trait Value: Parameters {
+	type Key: Hash + Eq;
+
+	/// Returns the key of the node, or `None` if it must never be merged,
+	/// such as when it has side effects. Nodes with equal keys and equal
+	/// parameters must compute the same results.
+	fn key(&self) -> Option<Self::Key>;
+}
+
+#[derive(Clone, Copy, Hash, PartialEq, Eq)]
+enum Operand {
+	Link(Link),
+	Bound { argument: Id, offset: u16 },
+}
+
+type Table<K> = HashMap<(K, Vec<Operand>), Vec<Link>>;
+
+/// A global value numberer.
+/// It merges simple nodes with equal keys and parameters, either within
+/// the same region or with a node of an enclosing region. Values of enclosing
+/// regions are passed down as new parameters of the compound nodes in between.
+pub struct ValueNumberer<K> {
+	region_finder: RegionFinder,
+
+	path: Vec<(Id, usize)>,
+	tables: Vec<Table<K>>,
+	members: Vec<Id>,
+	routed: Vec<Id>,
+}
+
+impl<K: Clone + Hash + Eq> ValueNumberer<K> {
+	/// Creates a new, reusable [`ValueNumberer`] instance.
+	#[inline]
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			region_finder: RegionFinder::new(),
+
+			path: Vec::new(),
+			tables: Vec::new(),
+			members: Vec::new(),
+			routed: Vec::new(),
+		}
+	}
+
+	// Region arguments that are the same for the whole region are followed
+	// outwards, so that equal values compare equal at any depth.
+	fn find_operand<T>(nodes: &DataFlowGraph<T>, mut link: Link) -> Operand {
+		while let Some((source, true)) = LambdaInliner::find_source(nodes, link) {
+			link = source;
+		}
+
+		let Some(argument) = nodes.get(link.node).and_then(Node::as_argument) else {
+			return Operand::Link(link);
+		};
+
+		// Ports after the parameters move up when a parameter is added.
+		let parameters = match nodes.get(argument.parent) {
+			Some(Node::Phi(phi)) => phi.parameters.len(),
+			Some(Node::Lambda(lambda)) => lambda.parameters.len(),
+			_ => 0,
+		};
+
+		Operand::Bound {
+			argument: link.node,
+			offset: link.port.saturating_sub(parameters.try_into().unwrap()),
+		}
+	}
+
+	fn find_value(&self, key: &(K, Vec<Operand>)) -> Option<(usize, Vec<Link>)> {
+		self.tables
+			.iter()
+			.enumerate()
+			.rev()
+			.find_map(|(depth, table)| Some((depth, table.get(key)?.clone())))
+	}
+
+	// Values already passed into a sibling region can be used again.
+	fn find_port<T>(nodes: &DataFlowGraph<T>, parent: Id, argument: Id, link: Link) -> Option<u16> {
+		let parameters = nodes[parent].as_parameters()?;
+
+		(0..parameters.len())
+			.filter(|&index| parameters[index] == link)
+			.map(|index| index.try_into().unwrap())
+			.find(|&port| {
+				let source = Link {
+					node: argument,
+					port,
+				};
+
+				LambdaInliner::find_source(nodes, source) == Some((link, true))
+			})
+	}
+
+	fn route<T>(
+		&mut self,
+		nodes: &mut DataFlowGraph<T>,
+		key: (K, Vec<Operand>),
+		depth: usize,
+		mut links: Vec<Link>,
+	) -> Vec<Link>
+	where
+		T: Parameters + ParametersMut,
+	{
+		for depth in depth + 1..self.tables.len() {
+			let (parent, region) = self.path[depth];
+			let argument = nodes[parent].as_arguments().unwrap()[region];
+
+			if !self.routed.contains(&parent) {
+				self.routed.push(parent);
+			}
+
+			for link in &mut links {
+				let port = Self::find_port(nodes, parent, argument, *link)
+					.unwrap_or_else(|| nodes.add_parameter(parent, *link));
+
+				*link = Link {
+					node: argument,
+					port,
+				};
+			}
+
+			self.tables[depth].insert(key.clone(), links.clone());
+		}
+
+		links
+	}
+
+	fn number_node<T>(&mut self, nodes: &mut DataFlowGraph<T>, id: Id) -> bool
+	where
+		T: Value<Key = K> + Arity + ParametersMut,
+	{
+		let Some(simple) = nodes.get(id).and_then(Node::as_simple) else {
+			return false;
+		};
+
+		let Some(key) = simple.key() else {
+			return false;
+		};
+
+		let operands = simple
+			.parameters()
+			.map(|&link| Self::find_operand(nodes, link))
+			.collect();
+
+		let key = (key, operands);
+		let Some((depth, links)) = self.find_value(&key) else {
+			let links = nodes.outputs(id).collect();
+
+			self.tables.last_mut().unwrap().insert(key, links);
+
+			return false;
+		};
+
+		let links = self.route(nodes, key, depth, links);
+
+		for (from, to) in nodes.outputs(id).zip(links) {
+			nodes.replace_uses(from, to);
+		}
+
+		nodes.remove_node(id);
+
+		true
+	}
+
+	fn number_region<T>(&mut self, nodes: &mut DataFlowGraph<T>, id: Id, region: usize) -> usize
+	where
+		T: Value<Key = K> + Arity + ParametersMut,
+	{
+		let start = self.members.len();
+
+		self.region_finder.run(nodes, id, region);
+		self.members.extend_from_slice(self.region_finder.nodes());
+
+		self.path.push((id, region));
+		self.tables.push(HashMap::new());
+
+		let mut merged = 0;
+
+		for index in start..self.members.len() {
+			let member = self.members[index];
+
+			if self.number_node(nodes, member) {
+				merged += 1;
+			} else if nodes.get(member).is_some() {
+				merged += self.number_regions(nodes, member);
+			}
+		}
+
+		self.path.pop();
+		self.tables.pop();
+		self.members.truncate(start);
+
+		merged
+	}
+
+	fn number_regions<T>(&mut self, nodes: &mut DataFlowGraph<T>, id: Id) -> usize
+	where
+		T: Value<Key = K> + Arity + ParametersMut,
+	{
+		let regions = nodes[id].as_results().map_or(0, <[_]>::len);
+
+		(0..regions)
+			.map(|region| self.number_region(nodes, id, region))
+			.sum()
+	}
+
+	// Values passed down may stop being read once a later value is merged
+	// in their place, so the parameters nobody reads anymore are removed.
+	fn prune_routed<T>(&self, nodes: &mut DataFlowGraph<T>)
+	where
+		T: Parameters + ParametersMut,
+	{
+		let tracked = nodes.users().is_some();
+
+		if !tracked {
+			nodes.track_users();
+		}
+
+		loop {
+			let mut changed = false;
+
+			for &id in &self.routed {
+				changed |= PortPruner::prune(nodes, id, true);
+			}
+
+			if !changed {
+				break;
+			}
+		}
+
+		if !tracked {
+			nodes.forget_users();
+		}
+	}
+
+	/// Merges equal nodes in every region of the compound node, including nested regions,
+	/// and returns the number of nodes removed. Every simple node of a region is numbered,
+	/// including nodes without parameters such as constants. Parameters that were passed
+	/// down and are no longer read are removed again.
+	pub fn run<T>(&mut self, nodes: &mut DataFlowGraph<T>, id: Id) -> usize
+	where
+		T: Value<Key = K> + Arity + ParametersMut,
+	{
+		self.path.clear();
+		self.tables.clear();
+		self.members.clear();
+		self.routed.clear();
+
+		let merged = self.number_regions(nodes, id);
+
+		if !self.routed.is_empty() {
+			self.prune_routed(nodes);
+		}
+
+		merged
+	}
+}
+
+impl<K: Clone + Hash + Eq> Default for ValueNumberer<K> {
+	#[inline]
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		collection::{
+			data_flow_graph::DataFlowGraph,
+			link::Link,
+			node::{Arity, Parameters, ParametersMut},
+		},
+		verify::verifier::Verifier,
+	};
+
+	use super::{Value, ValueNumberer};
+
+	enum Simple {
+		Constant(u64),
+		Add(Vec<Link>),
+		Print(Vec<Link>),
+	}
+
+	impl Parameters for Simple {
+		type Iter<'a> = std::slice::Iter<'a, Link>;
+
+		fn parameters(&self) -> Self::Iter<'_> {
+			match self {
+				Self::Constant(_) => [].iter(),
+				Self::Add(parameters) | Self::Print(parameters) => parameters.iter(),
+			}
+		}
+	}
+
+	impl ParametersMut for Simple {
+		type IterMut<'a> = std::slice::IterMut<'a, Link>;
+
+		fn parameters_mut(&mut self) -> Self::IterMut<'_> {
+			match self {
+				Self::Constant(_) => [].iter_mut(),
+				Self::Add(parameters) | Self::Print(parameters) => parameters.iter_mut(),
+			}
+		}
+	}
+
+	impl Arity for Simple {
+		fn arity(&self) -> usize {
+			1
+		}
+	}
+
+	impl Value for Simple {
+		type Key = Option<u64>;
+
+		fn key(&self) -> Option<Self::Key> {
+			match self {
+				Self::Constant(value) => Some(Some(*value)),
+				Self::Add(_) => Some(None),
+				Self::Print(_) => None,
+			}
+		}
+	}
+
+	#[test]
+	fn test_merges_into_enclosing_region() {
+		let mut nodes = DataFlowGraph::<Simple>::new();
+
+		let lambda = nodes.lambda(Vec::new(), 1, |lambda| {
+			let input = lambda.input(0);
+			let constant_0 = lambda.add_simple(Simple::Constant(1));
+			let constant_1 = lambda.add_simple(Simple::Constant(1));
+			let add_0 = lambda.add_simple(Simple::Add(vec![input, constant_0]));
+			let add_1 = lambda.add_simple(Simple::Add(vec![input, constant_1]));
+			let print_0 = lambda.add_simple(Simple::Print(vec![add_0]));
+			let print_1 = lambda.add_simple(Simple::Print(vec![add_1]));
+
+			let gamma = lambda.gamma(input, vec![input], 2, |gamma| {
+				let input = gamma.input(0);
+				let constant = gamma.add_simple(Simple::Constant(1));
+
+				vec![gamma.add_simple(Simple::Add(vec![input, constant]))]
+			});
+
+			vec![print_0, print_1, gamma]
+		});
+
+		assert_eq!(ValueNumberer::new().run(&mut nodes, lambda.node), 6);
+
+		let results = &nodes[lambda.node].as_lambda().unwrap().results;
+		let add = nodes[results[0].node].parameters().next().copied();
+		let gamma = nodes[results[2].node].as_gamma().unwrap();
+
+		assert_ne!(results[0], results[1]);
+		assert_eq!(nodes[results[1].node].parameters().next().copied(), add);
+		// Only the addition is still read, the input and the constant passed in before it are pruned.
+		assert_eq!(gamma.parameters.len(), 2);
+		assert_eq!(gamma.parameters[0], add.unwrap());
+
+		for (&argument, results) in gamma.arguments.iter().zip(gamma.results.iter()) {
+			let link = Link {
+				node: argument,
+				port: 0,
+			};
+
+			assert_eq!(results, &[link]);
+		}
+
+		assert!(Verifier::new().run(&nodes).is_empty());
+	}
+
+	#[test]
+	fn test_merges_nodes_without_inputs() {
+		let mut nodes = DataFlowGraph::<Simple>::new();
+
+		let lambda = nodes.lambda(Vec::new(), 1, |lambda| {
+			let constant_0 = lambda.add_simple(Simple::Constant(2));
+			let constant_1 = lambda.add_simple(Simple::Constant(2));
+			let add_0 = lambda.add_simple(Simple::Add(vec![constant_0, constant_0]));
+			let add_1 = lambda.add_simple(Simple::Add(vec![constant_1, constant_1]));
+
+			vec![
+				lambda.add_simple(Simple::Print(vec![add_0])),
+				lambda.add_simple(Simple::Print(vec![add_1])),
+			]
+		});
+
+		assert_eq!(ValueNumberer::new().run(&mut nodes, lambda.node), 2);
+
+		let results = &nodes[lambda.node].as_lambda().unwrap().results;
+		let add_0 = nodes[results[0].node].parameters().next().copied().unwrap();
+		let add_1 = nodes[results[1].node].parameters().next().copied().unwrap();
+
+		assert_eq!(add_0, add_1);
+		assert!(Verifier::new().run(&nodes).is_empty());
+	}
+}
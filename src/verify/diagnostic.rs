@@ -0,0 +1,114 @@
+use crate::collection::link::{Id, Link};
+
+/// A structural problem found in a graph.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum Diagnostic {
+	/// The `user` node refers to a node that is not in the graph.
+	DanglingLink { user: Id, link: Link },
+
+	/// The `user` node refers to a port that the node does not have.
+	PortOutOfRange { user: Id, link: Link, ports: usize },
+
+	/// The region `region` of the `id` node does not start at the `argument` node,
+	/// or the `argument` node does not refer back to it.
+	ArgumentMismatch { id: Id, region: usize, argument: Id },
+
+	/// The [`Gamma`] node has fewer than two regions.
+	///
+	/// [`Gamma`]: crate::collection::node::Gamma
+	GammaRegionCount { id: Id, regions: usize },
+
+	/// The [`Gamma`] node has no parameter to use as its predicate.
+	///
+	/// [`Gamma`]: crate::collection::node::Gamma
+	GammaPredicateMissing { id: Id },
+
+	/// The [`Gamma`] node has a region whose result count differs from the first region.
+	///
+	/// [`Gamma`]: crate::collection::node::Gamma
+	GammaResultCount {
+		id: Id,
+		region: usize,
+		expected: usize,
+		found: usize,
+	},
+
+	/// The [`Theta`] node does not have one result per parameter followed by its predicate.
+	///
+	/// [`Theta`]: crate::collection::node::Theta
+	ThetaPredicateMissing {
+		id: Id,
+		parameters: usize,
+		results: usize,
+	},
+
+	/// The [`Delta`] node does not have exactly one result.
+	///
+	/// [`Delta`]: crate::collection::node::Delta
+	DeltaResultCount { id: Id, results: usize },
+
+	/// The [`Omega`] node does not have one result per export name.
+	///
+	/// [`Omega`]: crate::collection::node::Omega
+	OmegaExportCount {
+		id: Id,
+		exports: usize,
+		results: usize,
+	},
+
+	/// The node depends on itself.
+	Cycle { id: Id },
+}
+
+impl std::fmt::Display for Diagnostic {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match *self {
+			Self::DanglingLink { user, link } => {
+				write!(f, "node {user} refers to missing node {}", link.node)
+			}
+			Self::PortOutOfRange { user, link, ports } => write!(
+				f,
+				"node {user} refers to port {} of node {} which has {ports} ports",
+				link.port, link.node
+			),
+			Self::ArgumentMismatch {
+				id,
+				region,
+				argument,
+			} => write!(
+				f,
+				"node {id} region {region} does not match argument {argument}"
+			),
+			Self::GammaRegionCount { id, regions } => {
+				write!(f, "gamma {id} has {regions} regions instead of at least 2")
+			}
+			Self::GammaPredicateMissing { id } => write!(f, "gamma {id} has no predicate"),
+			Self::GammaResultCount {
+				id,
+				region,
+				expected,
+				found,
+			} => write!(
+				f,
+				"gamma {id} region {region} has {found} results instead of {expected}"
+			),
+			Self::ThetaPredicateMissing {
+				id,
+				parameters,
+				results,
+			} => write!(
+				f,
+				"theta {id} has {results} results for {parameters} parameters and a predicate"
+			),
+			Self::DeltaResultCount { id, results } => {
+				write!(f, "delta {id} has {results} results instead of 1")
+			}
+			Self::OmegaExportCount {
+				id,
+				exports,
+				results,
+			} => write!(f, "omega {id} has {results} results for {exports} exports"),
+			Self::Cycle { id } => write!(f, "node {id} depends on itself"),
+		}
+	}
+}
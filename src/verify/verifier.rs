@@ -0,0 +1,383 @@
+use std::ops::Range;
+
+use crate::collection::{
+	data_flow_graph::DataFlowGraph,
+	link::{Id, Link},
+	node::{Arity, Node, Parameters},
+};
+
+use super::diagnostic::Diagnostic;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum State {
+	Unvisited,
+	Active,
+	Done,
+}
+
+struct Visit {
+	id: Id,
+	start: usize,
+	successors: Range<usize>,
+}
+
+/// A structural verifier for graphs.
+/// It reports every problem found instead of stopping at the first one.
+pub struct Verifier {
+	diagnostics: Vec<Diagnostic>,
+	states: Vec<State>,
+	visits: Vec<Visit>,
+	successors: Vec<Id>,
+}
+
+impl Verifier {
+	/// Creates a new, reusable [`Verifier`] instance.
+	#[inline]
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			diagnostics: Vec::new(),
+			states: Vec::new(),
+			visits: Vec::new(),
+			successors: Vec::new(),
+		}
+	}
+
+	/// Returns the diagnostics found by the last run.
+	#[must_use]
+	pub fn diagnostics(&self) -> &[Diagnostic] {
+		&self.diagnostics
+	}
+
+	fn verify_link<T: Arity>(&mut self, nodes: &DataFlowGraph<T>, user: Id, link: Link) {
+		if nodes.get(link.node).is_none() {
+			self.diagnostics
+				.push(Diagnostic::DanglingLink { user, link });
+
+			return;
+		}
+
+		if let Some(ports) = nodes.arity(link.node) {
+			if usize::from(link.port) >= ports {
+				self.diagnostics
+					.push(Diagnostic::PortOutOfRange { user, link, ports });
+			}
+		}
+	}
+
+	fn verify_links<T: Parameters + Arity>(&mut self, nodes: &DataFlowGraph<T>, id: Id) {
+		let node = &nodes[id];
+
+		for &link in node.parameters() {
+			self.verify_link(nodes, id, link);
+		}
+
+		// Results that garbage collection found unread are left dangling on purpose.
+		let results = node.as_results().unwrap_or_default().iter().flatten();
+
+		for &link in results.filter(|&&link| link != Link::dangling()) {
+			self.verify_link(nodes, id, link);
+		}
+	}
+
+	fn verify_arguments<T>(&mut self, nodes: &DataFlowGraph<T>, id: Id, node: &Node<T>) {
+		if let Node::Argument(argument) = node {
+			let arguments = nodes.get(argument.parent).and_then(Node::as_arguments);
+
+			if arguments.and_then(|list| list.get(argument.region)) != Some(&id) {
+				self.diagnostics.push(Diagnostic::ArgumentMismatch {
+					id: argument.parent,
+					region: argument.region,
+					argument: id,
+				});
+			}
+
+			return;
+		}
+
+		let arguments = node.as_arguments().unwrap_or_default();
+		let regions = node.as_results().map_or(0, <[_]>::len);
+
+		for region in 0..regions.max(arguments.len()) {
+			let argument = arguments.get(region).copied().unwrap_or(Id::dangling());
+			let start = nodes.get(argument).and_then(Node::as_argument);
+
+			if !start.is_some_and(|start| start.parent == id && start.region == region) {
+				self.diagnostics.push(Diagnostic::ArgumentMismatch {
+					id,
+					region,
+					argument,
+				});
+			}
+		}
+	}
+
+	fn verify_shape<T>(&mut self, id: Id, node: &Node<T>) {
+		match node {
+			Node::Gamma(gamma) => {
+				if gamma.parameters.is_empty() {
+					self.diagnostics
+						.push(Diagnostic::GammaPredicateMissing { id });
+				}
+
+				if gamma.results.len() < 2 {
+					self.diagnostics.push(Diagnostic::GammaRegionCount {
+						id,
+						regions: gamma.results.len(),
+					});
+				}
+
+				let Some((first, rest)) = gamma.results.split_first() else {
+					return;
+				};
+
+				for (region, results) in rest.iter().enumerate() {
+					if results.len() != first.len() {
+						self.diagnostics.push(Diagnostic::GammaResultCount {
+							id,
+							region: region + 1,
+							expected: first.len(),
+							found: results.len(),
+						});
+					}
+				}
+			}
+			Node::Theta(theta) if theta.results.len() != theta.parameters.len() + 1 => {
+				self.diagnostics.push(Diagnostic::ThetaPredicateMissing {
+					id,
+					parameters: theta.parameters.len(),
+					results: theta.results.len(),
+				});
+			}
+			Node::Delta(delta) if delta.results.len() != 1 => {
+				self.diagnostics.push(Diagnostic::DeltaResultCount {
+					id,
+					results: delta.results.len(),
+				});
+			}
+			Node::Omega(omega) if omega.results.len() != omega.exports.len() => {
+				self.diagnostics.push(Diagnostic::OmegaExportCount {
+					id,
+					exports: omega.exports.len(),
+					results: omega.results.len(),
+				});
+			}
+			_ => {}
+		}
+	}
+
+	fn queue_visit<T: Parameters>(&mut self, nodes: &DataFlowGraph<T>, id: Id) {
+		let node = &nodes[id];
+		let start = self.successors.len();
+		let parameters = node.parameters();
+		let results = node.as_results().unwrap_or_default().iter().flatten();
+
+		self.successors.extend(
+			parameters
+				.chain(results)
+				.map(|link| link.node)
+				.filter(|&id| nodes.get(id).is_some()),
+		);
+
+		self.states[id] = State::Active;
+		self.visits.push(Visit {
+			id,
+			start,
+			successors: start..self.successors.len(),
+		});
+	}
+
+	fn find_cycles_from<T: Parameters>(&mut self, nodes: &DataFlowGraph<T>, start: Id) {
+		self.queue_visit(nodes, start);
+
+		while let Some(visit) = self.visits.last_mut() {
+			if let Some(index) = visit.successors.next() {
+				let id = self.successors[index];
+
+				match self.states[id] {
+					State::Unvisited => self.queue_visit(nodes, id),
+					State::Active => self.diagnostics.push(Diagnostic::Cycle { id }),
+					State::Done => {}
+				}
+			} else {
+				self.states[visit.id] = State::Done;
+				self.successors.truncate(visit.start);
+				self.visits.pop();
+			}
+		}
+	}
+
+	/// Verifies every node of the graph and returns the diagnostics found.
+	///
+	/// Loops are expressed through [`Theta`] nodes and never as back edges,
+	/// so any cycle found is reported as illegal.
+	///
+	/// [`Theta`]: crate::collection::node::Theta
+	pub fn run<T: Parameters + Arity>(&mut self, nodes: &DataFlowGraph<T>) -> &[Diagnostic] {
+		self.diagnostics.clear();
+		self.states.clear();
+		self.states.resize(nodes.indices_needed(), State::Unvisited);
+
+		for (id, node) in nodes.iter() {
+			self.verify_links(nodes, id);
+			self.verify_arguments(nodes, id, node);
+			self.verify_shape(id, node);
+		}
+
+		for (id, _) in nodes.iter() {
+			if self.states[id] == State::Unvisited {
+				self.find_cycles_from(nodes, id);
+			}
+		}
+
+		&self.diagnostics
+	}
+}
+
+impl Default for Verifier {
+	#[inline]
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::collection::{
+		data_flow_graph::DataFlowGraph,
+		link::Link,
+		node::{Arity, Node, Parameters},
+	};
+
+	use super::{Diagnostic, Verifier};
+
+	enum Simple {
+		Leaf,
+		Ref(Link),
+	}
+
+	impl Parameters for Simple {
+		type Iter<'a> = std::option::IntoIter<&'a Link>;
+
+		fn parameters(&self) -> Self::Iter<'_> {
+			let parameters = match self {
+				Self::Leaf => None,
+				Self::Ref(link) => Some(link),
+			};
+
+			parameters.into_iter()
+		}
+	}
+
+	impl Arity for Simple {
+		fn arity(&self) -> usize {
+			1
+		}
+	}
+
+	#[test]
+	fn test_reports_malformed() {
+		let mut nodes = DataFlowGraph::<Simple>::new();
+
+		let node_0 = nodes.add_simple(Simple::Leaf);
+		let node_1 = nodes.add_simple(Simple::Leaf);
+		let node_2 = nodes.add_simple(Simple::Ref(node_1));
+		let argument_3 = nodes.add_argument();
+		let node_3 = nodes.add_gamma(
+			Vec::new(),
+			[argument_3.node].into_iter().collect(),
+			[vec![node_0]].into_iter().collect(),
+		);
+		let node_4 = nodes.add_theta(vec![node_0], argument_3.node, vec![node_0]);
+		let node_5 = nodes.add_simple(Simple::Ref(Link {
+			node: node_3.node,
+			port: 1,
+		}));
+		let node_6 = nodes.add_simple(Simple::Ref(Link {
+			node: node_0.node,
+			port: 1,
+		}));
+
+		let _ = nodes.remove(node_1.node);
+
+		let mut verifier = Verifier::new();
+		let diagnostics = verifier.run(&nodes);
+
+		assert!(diagnostics.contains(&Diagnostic::DanglingLink {
+			user: node_2.node,
+			link: node_1,
+		}));
+		assert!(diagnostics.contains(&Diagnostic::ArgumentMismatch {
+			id: node_3.node,
+			region: 0,
+			argument: argument_3.node,
+		}));
+		assert!(diagnostics.contains(&Diagnostic::GammaPredicateMissing { id: node_3.node }));
+		assert!(diagnostics.contains(&Diagnostic::GammaRegionCount {
+			id: node_3.node,
+			regions: 1,
+		}));
+		assert!(diagnostics.contains(&Diagnostic::ThetaPredicateMissing {
+			id: node_4.node,
+			parameters: 1,
+			results: 1,
+		}));
+		assert!(diagnostics.contains(&Diagnostic::PortOutOfRange {
+			user: node_5.node,
+			link: Link {
+				node: node_3.node,
+				port: 1,
+			},
+			ports: 1,
+		}));
+		assert!(diagnostics.contains(&Diagnostic::PortOutOfRange {
+			user: node_6.node,
+			link: Link {
+				node: node_0.node,
+				port: 1,
+			},
+			ports: 1,
+		}));
+	}
+
+	#[test]
+	fn test_accepts_collected_results() {
+		let mut nodes = DataFlowGraph::<Simple>::new();
+
+		let node_0 = nodes.add_simple(Simple::Leaf);
+		let node_1 = nodes.add_simple(Simple::Leaf);
+		let arguments = [nodes.add_argument().node, nodes.add_argument().node];
+		let node_2 = nodes.add_gamma(
+			vec![node_0],
+			arguments.into_iter().collect(),
+			[vec![node_0, node_1], vec![node_0, node_1]]
+				.into_iter()
+				.collect(),
+		);
+		let node_3 = nodes.add_simple(Simple::Ref(node_2));
+
+		nodes.track_users();
+
+		assert_eq!(nodes.collect_garbage([node_3.node]), [node_1.node]);
+		assert!(nodes.users_of(Link::dangling()).is_empty());
+		assert!(Verifier::new().run(&nodes).is_empty());
+	}
+
+	#[test]
+	fn test_reports_cycle() {
+		let mut nodes = DataFlowGraph::<Simple>::new();
+
+		let node_0 = nodes.add_simple(Simple::Leaf);
+		let node_1 = nodes.add_simple(Simple::Ref(node_0));
+
+		nodes[node_0.node] = Node::Simple(Simple::Ref(node_1));
+
+		let mut verifier = Verifier::new();
+
+		assert!(!verifier.run(&nodes).is_empty());
+		assert!(verifier
+			.diagnostics()
+			.iter()
+			.all(|diagnostic| matches!(diagnostic, Diagnostic::Cycle { .. })));
+	}
+}
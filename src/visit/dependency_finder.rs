@@ -0,0 +1,55 @@
+use std::collections::HashSet;
+
+use crate::collection::{data_flow_graph::DataFlowGraph, link::Id, node::Parameters};
+
+/// A dependency finder.
+/// It finds whether a node depends on another through its parameters or
+/// the results of its regions, since regions may use nodes outside of them.
+pub struct DependencyFinder {
+	seen: HashSet<Id>,
+	stack: Vec<Id>,
+}
+
+impl DependencyFinder {
+	/// Creates a new, reusable [`DependencyFinder`] instance.
+	#[inline]
+	#[must_use]
+	pub fn new() -> Self {
+		Self {
+			seen: HashSet::new(),
+			stack: Vec::new(),
+		}
+	}
+
+	/// Returns whether the `id` node depends on the `other` node.
+	pub fn run<T: Parameters>(&mut self, nodes: &DataFlowGraph<T>, id: Id, other: Id) -> bool {
+		self.seen.clear();
+		self.stack.clear();
+		self.stack.push(id);
+
+		while let Some(id) = self.stack.pop() {
+			let Some(node) = nodes.get(id) else { continue };
+
+			let results = node.as_results().unwrap_or_default();
+
+			for link in node.parameters().chain(results.iter().flatten()) {
+				if link.node == other {
+					return true;
+				}
+
+				if self.seen.insert(link.node) {
+					self.stack.push(link.node);
+				}
+			}
+		}
+
+		false
+	}
+}
+
+impl Default for DependencyFinder {
+	#[inline]
+	fn default() -> Self {
+		Self::new()
+	}
+}
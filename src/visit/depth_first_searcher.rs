@@ -92,7 +92,8 @@ impl DepthFirstSearcher {
 	fn queue_node<T: Parameters>(&mut self, nodes: &DataFlowGraph<T>, id: Id) {
 		let index = id.index().try_into_unchecked();
 
-		if !self.nodes.remove(index).unwrap_or(false) {
+		// Dangling links, such as unread results, have no node to visit.
+		if nodes.get(id).is_none() || !self.nodes.remove(index).unwrap_or(false) {
 			return;
 		}
 
@@ -176,8 +177,8 @@ mod tests {
 	#[test]
 	fn test_is_in_order() {
 		let mut nodes = DataFlowGraph::<Simple>::new();
-		let mut real = [0; 6];
-		let mut result = [0; 6];
+		let mut real = [0; 8];
+		let mut result = [0; 8];
 
 		let node_0 = nodes.add_simple(Simple::Leaf);
 		let node_1 = nodes.add_simple(Simple::Ref(node_0));
@@ -192,8 +193,10 @@ mod tests {
 		real[node_3.node] = 6;
 
 		let node_4 = nodes.add_simple(Simple::Leaf);
+		let arguments = [nodes.add_argument().node, nodes.add_argument().node];
 		let node_5 = nodes.add_gamma(
 			vec![node_4],
+			arguments.into_iter().collect(),
 			[vec![node_1], vec![node_2, node_3]].into_iter().collect(),
 		);
 
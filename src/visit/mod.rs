@@ -1,2 +1,4 @@
+pub mod dependency_finder;
 pub mod depth_first_searcher;
+pub mod region_finder;
 pub mod successor_finder;
@@ -0,0 +1,282 @@
+use std::ops::Range;
+
+use arena::referent::{Referent, Similar};
+
+use crate::collection::{
+	data_flow_graph::DataFlowGraph,
+	link::{Id, Link},
+	node::Parameters,
+};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum State {
+	Unvisited,
+	Active(usize),
+	Nested { owner: Id, reads: bool },
+	Reads,
+	Free,
+	Outside,
+	Inside,
+}
+
+struct Visit {
+	id: Id,
+	start: usize,
+	parameters: Range<usize>,
+}
+
+/// A region member finder.
+/// It caches the nodes of a region, which are the nodes reachable from its results
+/// that do not belong to a nested region.
+///
+/// Nodes that depend on the [`Argument`] of the region, directly or through the regions
+/// of a compound node, are always members. Other nodes, such as constants, are members
+/// only if all of their uses are inside of the region, so that those of enclosing regions
+/// read directly are left out.
+///
+/// [`Argument`]: crate::collection::node::Argument
+pub struct RegionFinder {
+	parent: Id,
+	region: usize,
+	argument: Id,
+
+	nodes: Vec<Id>,
+	states: Vec<State>,
+
+	visits: Vec<Visit>,
+	parameters: Vec<Id>,
+}
+
+impl RegionFinder {
+	/// Creates a new, reusable [`RegionFinder`] instance.
+	#[inline]
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			parent: Id::dangling(),
+			region: 0,
+			argument: Id::dangling(),
+
+			nodes: Vec::new(),
+			states: Vec::new(),
+
+			visits: Vec::new(),
+			parameters: Vec::new(),
+		}
+	}
+
+	/// Returns the node owning the region of the last run.
+	#[must_use]
+	pub const fn parent(&self) -> Id {
+		self.parent
+	}
+
+	/// Returns the index of the region of the last run.
+	#[must_use]
+	pub const fn region(&self) -> usize {
+		self.region
+	}
+
+	/// Returns the nodes of the region, with every node after its parameters.
+	#[must_use]
+	pub fn nodes(&self) -> &[Id] {
+		&self.nodes
+	}
+
+	/// Returns whether the node belongs to the region.
+	#[must_use]
+	pub fn contains(&self, id: Id) -> bool {
+		self.state(id) == State::Inside
+	}
+
+	fn state(&self, id: Id) -> State {
+		let index: usize = id.index().try_into_unchecked();
+
+		self.states.get(index).copied().unwrap_or(State::Outside)
+	}
+
+	fn depth(&self, id: Id) -> usize {
+		match self.state(id) {
+			State::Active(depth) => depth,
+			_ => 0,
+		}
+	}
+
+	fn mark_outside<'a, I: IntoIterator<Item = &'a Link>>(&mut self, links: I) {
+		for link in links {
+			let index: usize = link.node.index().try_into_unchecked();
+
+			if let Some(state @ State::Free) = self.states.get_mut(index) {
+				*state = State::Outside;
+			}
+		}
+	}
+
+	fn queue_node<T: Parameters>(&mut self, nodes: &DataFlowGraph<T>, id: Id) {
+		// Dangling links, such as unread results, have no node to visit.
+		let Some(node) = nodes.get(id) else { return };
+
+		if self.state(id) != State::Unvisited {
+			return;
+		}
+
+		let start = self.parameters.len();
+		let results = node.as_results().into_iter().flatten().flatten();
+
+		// Nested regions are entered so that compound nodes reading the region are found.
+		self.states[id] = State::Active(self.visits.len());
+		self.parameters
+			.extend(node.parameters().chain(results).map(|link| link.node));
+
+		self.visits.push(Visit {
+			id,
+			start,
+			parameters: start..self.parameters.len(),
+		});
+	}
+
+	fn find_state<T>(&self, nodes: &DataFlowGraph<T>, id: Id, start: usize) -> State {
+		if id == self.argument {
+			return State::Reads;
+		}
+
+		// Arguments of nested regions are only reached through their parent.
+		if let Some(argument) = nodes[id].as_argument() {
+			return match self.state(argument.parent) {
+				State::Active(_) => State::Nested {
+					owner: argument.parent,
+					reads: false,
+				},
+				_ => State::Outside,
+			};
+		}
+
+		let mut reads = false;
+		let mut owner = None;
+
+		for &parameter in &self.parameters[start..] {
+			match self.state(parameter) {
+				State::Reads => reads = true,
+				State::Nested {
+					owner: nested,
+					reads: nested_reads,
+				} => {
+					reads |= nested_reads;
+
+					// A node belongs to the innermost region it reads.
+					if nested != id
+						&& owner.is_none_or(|owner| self.depth(nested) > self.depth(owner))
+					{
+						owner = Some(nested);
+					}
+				}
+				_ => {}
+			}
+		}
+
+		match owner {
+			Some(owner) => State::Nested { owner, reads },
+			None if reads => State::Reads,
+			None => State::Free,
+		}
+	}
+
+	fn find_outer_uses<T: Parameters>(&mut self, nodes: &DataFlowGraph<T>) {
+		for (user, node) in nodes.iter() {
+			let results = node.as_results().unwrap_or_default();
+
+			if user == self.parent {
+				let current = self.region;
+				let others = results
+					.iter()
+					.enumerate()
+					.filter(|&(region, _)| region != current)
+					.flat_map(|(_, results)| results);
+
+				self.mark_outside(node.parameters().chain(others));
+			} else if matches!(self.state(user), State::Unvisited | State::Outside) {
+				self.mark_outside(node.parameters().chain(results.iter().flatten()));
+			}
+		}
+	}
+
+	fn find_members<T: Parameters>(&mut self, nodes: &DataFlowGraph<T>) {
+		if self.nodes.iter().any(|&id| self.state(id) == State::Free) {
+			self.find_outer_uses(nodes);
+		}
+
+		// Users come before their parameters, so every use of a node is known when reached.
+		for index in (0..self.nodes.len()).rev() {
+			let id = self.nodes[index];
+			let state = match self.state(id) {
+				State::Reads | State::Free => State::Inside,
+				State::Nested { owner, .. } if self.state(owner) == State::Outside => {
+					State::Outside
+				}
+				state => state,
+			};
+
+			self.states[id] = state;
+
+			if state == State::Outside {
+				let node = &nodes[id];
+				let results = node.as_results().unwrap_or_default();
+
+				self.mark_outside(node.parameters().chain(results.iter().flatten()));
+			}
+		}
+
+		let states = &self.states;
+
+		self.nodes.retain(|&id| states[id] == State::Inside);
+	}
+
+	/// Finds and caches all nodes of the region `region` of the `parent` node.
+	pub fn run<T: Parameters>(&mut self, nodes: &DataFlowGraph<T>, parent: Id, region: usize) {
+		self.parent = parent;
+		self.region = region;
+
+		self.nodes.clear();
+		self.states.clear();
+		self.states.resize(nodes.indices_needed(), State::Unvisited);
+
+		let node = &nodes[parent];
+		let results = node.as_results().unwrap_or_default();
+
+		self.argument = node
+			.as_arguments()
+			.and_then(|arguments| arguments.get(region).copied())
+			.unwrap_or(Id::dangling());
+
+		for link in results.get(region).into_iter().flatten() {
+			self.queue_node(nodes, link.node);
+
+			while let Some(visit) = self.visits.last_mut() {
+				if let Some(parameter) = visit.parameters.next() {
+					self.queue_node(nodes, self.parameters[parameter]);
+				} else {
+					let Visit { id, start, .. } = *visit;
+					let state = self.find_state(nodes, id, start);
+
+					self.states[id] = state;
+
+					if state != State::Outside {
+						self.nodes.push(id);
+					}
+
+					self.parameters.truncate(start);
+					self.visits.pop();
+				}
+			}
+		}
+
+		self.find_members(nodes);
+	}
+}
+
+impl Default for RegionFinder {
+	#[inline]
+	fn default() -> Self {
+		Self::new()
+	}
+}